@@ -0,0 +1,48 @@
+/* Coulomb counter, current is positive when discharging. Totals accumulate in f64: an f32
+sum of small current * dt products stops growing once the total is about 2^24 times the
+increment, f64 keeps full f32 resolution of each increment for far longer than any run. */
+pub struct ChargeCounter {
+    net: f64,        /* Signed charge in coulombs, positive for net discharge */
+    throughput: f64, /* Charge moved in either direction in coulombs */
+}
+
+impl ChargeCounter {
+    pub fn new() -> ChargeCounter {
+        ChargeCounter {
+            net: 0.0,
+            throughput: 0.0,
+        }
+    }
+    /* Non-finite or negative-time samples are ignored so one bad reading cannot poison the
+    totals */
+    pub fn accumulate(&mut self, current_a: f32, dt_s: f32) {
+        if !current_a.is_finite() || !dt_s.is_finite() || dt_s < 0.0 {
+            return;
+        }
+        let charge = current_a as f64 * dt_s as f64;
+        self.net += charge;
+        self.throughput += charge.abs();
+    }
+    pub fn as_coulombs(&self) -> f32 {
+        self.net as f32
+    }
+    pub fn as_ah(&self) -> f32 {
+        (self.net / 3600.0) as f32
+    }
+    pub fn throughput_coulombs(&self) -> f32 {
+        self.throughput as f32
+    }
+    pub fn throughput_ah(&self) -> f32 {
+        (self.throughput / 3600.0) as f32
+    }
+    pub fn reset(&mut self) {
+        self.net = 0.0;
+        self.throughput = 0.0;
+    }
+}
+
+impl Default for ChargeCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
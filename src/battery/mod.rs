@@ -0,0 +1,2 @@
+pub mod charge_counter;
+pub mod relaxation;
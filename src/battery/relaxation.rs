@@ -0,0 +1,57 @@
+use crate::math::float::exp;
+
+/* Two RC branch polarization model, current is positive when discharging */
+pub struct RelaxationModel {
+    tau_fast: f32, /* Fast branch time constant in seconds */
+    tau_slow: f32, /* Slow branch time constant in seconds */
+    r0: f32,       /* Ohmic resistance */
+    r_fast: f32,   /* Fast branch resistance */
+    r_slow: f32,   /* Slow branch resistance */
+    v_fast: f32,   /* Fast branch polarization voltage */
+    v_slow: f32,   /* Slow branch polarization voltage */
+    offset: f32,   /* Terminal voltage minus OCV from the last update */
+}
+
+impl RelaxationModel {
+    pub fn new(tau_fast: f32, tau_slow: f32) -> RelaxationModel {
+        RelaxationModel {
+            tau_fast,
+            tau_slow,
+            r0: 0.0,
+            r_fast: 0.0,
+            r_slow: 0.0,
+            v_fast: 0.0,
+            v_slow: 0.0,
+            offset: 0.0,
+        }
+    }
+    pub fn set_resistances(&mut self, r0: f32, r_fast: f32, r_slow: f32) {
+        self.r0 = r0;
+        self.r_fast = r_fast;
+        self.r_slow = r_slow;
+    }
+    pub fn reset(&mut self) {
+        self.v_fast = 0.0;
+        self.v_slow = 0.0;
+        self.offset = 0.0;
+    }
+    pub fn update(&mut self, current: f32, dt: f32) -> f32 {
+        // Exact zero-order-hold step of each RC branch, stable for any dt
+        let a_fast = exp(-dt / self.tau_fast);
+        let a_slow = exp(-dt / self.tau_slow);
+        self.v_fast = a_fast * self.v_fast + (1.0 - a_fast) * self.r_fast * current;
+        self.v_slow = a_slow * self.v_slow + (1.0 - a_slow) * self.r_slow * current;
+        self.offset = -(self.r0 * current + self.v_fast + self.v_slow);
+        self.offset
+    }
+    pub fn get_offset(&self) -> f32 {
+        self.offset
+    }
+    pub fn get_polarization(&self) -> (f32, f32) {
+        (self.v_fast, self.v_slow)
+    }
+    /* Relaxed OCV estimate while resting, adds back the polarization that has yet to decay */
+    pub fn predict_rest_ocv(&self, terminal_voltage: f32) -> f32 {
+        terminal_voltage + self.v_fast + self.v_slow
+    }
+}
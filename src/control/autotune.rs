@@ -0,0 +1,179 @@
+use crate::math::float::sqrt;
+use core::f32::consts::PI;
+
+/* Relay feedback auto-tuner (Astrom-Hagglund). The relay drives the plant around the
+setpoint into a limit cycle whose period and amplitude give the ultimate gain and period,
+from which Ziegler-Nichols PI gains follow. The gains are in the parallel form
+u = kp e + ki integral(e dt), as taken by control::pid::PID::new(kp, ki, 0.0). */
+pub struct RelayTuner {
+    amplitude: f32,            /* Relay swing around the bias */
+    bias: f32,                 /* Output the relay swings around */
+    setpoint: f32,             /* Measurement the oscillation is centered on */
+    hysteresis: f32,           /* Error band the relay ignores, rejects noise chatter */
+    sample_time: f32,          /* 1/Frequency of calling update */
+    out_min: f32,              /* Lower output limit */
+    out_max: f32,              /* Upper output limit */
+    max_deviation: f32,        /* Error beyond which tuning is aborted */
+    cycles: u32,               /* Cycles averaged after the first one is discarded */
+    relay_high: bool,          /* Relay state */
+    started: bool,             /* First rising switch seen, cycle timing has begun */
+    cycle_time: f32,           /* Time since the last rising switch */
+    cycles_seen: u32,          /* Complete cycles since the start */
+    period_sum: f32,           /* Sum of the averaged cycle periods */
+    swing_sum: f32,            /* Sum of the averaged cycle half peak-to-peak amplitudes */
+    y_max: f32,                /* Measurement maximum in the current cycle */
+    y_min: f32,                /* Measurement minimum in the current cycle */
+    output: f32,               /* Output to apply to the plant */
+    gains: Option<(f32, f32)>, /* Tuning result */
+    aborted: bool,             /* Measurement left the allowed deviation */
+}
+
+impl RelayTuner {
+    pub fn new(relay_amplitude: f32) -> RelayTuner {
+        RelayTuner {
+            amplitude: relay_amplitude.abs(),
+            bias: 0.0,
+            setpoint: 0.0,
+            hysteresis: 0.0,
+            sample_time: 1.0,
+            out_min: f32::NEG_INFINITY,
+            out_max: f32::INFINITY,
+            max_deviation: f32::INFINITY,
+            cycles: 3,
+            relay_high: false,
+            started: false,
+            cycle_time: 0.0,
+            cycles_seen: 0,
+            period_sum: 0.0,
+            swing_sum: 0.0,
+            y_max: f32::NEG_INFINITY,
+            y_min: f32::INFINITY,
+            output: 0.0,
+            gains: None,
+            aborted: false,
+        }
+    }
+    pub fn set_setpoint(&mut self, setpoint: f32) {
+        self.setpoint = setpoint;
+    }
+    /* Usually the output that held the plant near the setpoint before tuning */
+    pub fn set_bias(&mut self, bias: f32) {
+        self.bias = bias;
+        self.output = bias;
+    }
+    pub fn set_hysteresis(&mut self, hysteresis: f32) {
+        self.hysteresis = hysteresis.abs();
+    }
+    /* Without a sample time the period, and so ki, is in calls rather than seconds */
+    pub fn set_sample_time(&mut self, sample_time: f32) {
+        self.sample_time = sample_time;
+    }
+    /* Hard bounds on the relay output, whatever the bias and amplitude */
+    pub fn set_output_limits(&mut self, out_min: f32, out_max: f32) {
+        self.out_min = out_min;
+        self.out_max = out_max;
+    }
+    /* Tuning stops and the output returns to the bias if |setpoint - measurement| exceeds
+    this */
+    pub fn set_max_deviation(&mut self, deviation: f32) {
+        self.max_deviation = deviation.abs();
+    }
+    pub fn set_cycles(&mut self, cycles: u32) {
+        self.cycles = if cycles < 1 { 1 } else { cycles };
+    }
+    pub fn reset(&mut self) {
+        self.relay_high = false;
+        self.started = false;
+        self.cycle_time = 0.0;
+        self.cycles_seen = 0;
+        self.period_sum = 0.0;
+        self.swing_sum = 0.0;
+        self.y_max = f32::NEG_INFINITY;
+        self.y_min = f32::INFINITY;
+        self.output = self.bias;
+        self.gains = None;
+        self.aborted = false;
+    }
+    /* Call once per sample and apply get_output to the plant, returns the PI gains once
+    enough cycles are observed, after which the output rests at the bias */
+    pub fn update(&mut self, measurement: f32) -> Option<(f32, f32)> {
+        if self.gains.is_some() || self.aborted {
+            return self.gains;
+        }
+        let error = self.setpoint - measurement;
+        if error.abs() > self.max_deviation {
+            self.aborted = true;
+            self.output = self.bias;
+            return None;
+        }
+
+        self.cycle_time += self.sample_time;
+        self.y_max = self.y_max.max(measurement);
+        self.y_min = self.y_min.min(measurement);
+        let was_high = self.relay_high;
+        if error > self.hysteresis {
+            self.relay_high = true;
+        } else if error < -self.hysteresis {
+            self.relay_high = false;
+        }
+
+        // Cycles run from one rising switch to the next, the first one is a start-up
+        // transient and is discarded
+        if self.relay_high && !was_high {
+            if self.started {
+                self.cycles_seen += 1;
+                if self.cycles_seen > 1 {
+                    self.period_sum += self.cycle_time;
+                    self.swing_sum += 0.5 * (self.y_max - self.y_min);
+                }
+            }
+            self.started = true;
+            self.cycle_time = 0.0;
+            self.y_max = measurement;
+            self.y_min = measurement;
+            if self.cycles_seen > self.cycles {
+                self.gains = self.compute_gains();
+                self.output = self.bias;
+                return self.gains;
+            }
+        }
+
+        let output = if self.relay_high {
+            self.bias + self.amplitude
+        } else {
+            self.bias - self.amplitude
+        };
+        self.output = if output > self.out_max {
+            self.out_max
+        } else if output < self.out_min {
+            self.out_min
+        } else {
+            output
+        };
+        None
+    }
+    fn compute_gains(&self) -> Option<(f32, f32)> {
+        let n = self.cycles as f32;
+        let period = self.period_sum / n;
+        let swing = self.swing_sum / n;
+        // Describing function of a relay with hysteresis, Ku = 4 d / (pi sqrt(a^2 - eps^2))
+        let effective = swing * swing - self.hysteresis * self.hysteresis;
+        if effective <= 0.0 || period <= 0.0 {
+            return None;
+        }
+        let ku = 4.0 * self.amplitude / (PI * sqrt(effective));
+        // Ziegler-Nichols PI, kp = 0.45 Ku and Ti = Pu / 1.2
+        let kp = 0.45 * ku;
+        Some((kp, 1.2 * kp / period))
+    }
+    pub fn get_output(&self) -> f32 {
+        self.output
+    }
+    pub fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+    /* Ultimate gain and period from the measured cycles, once tuning has finished */
+    pub fn get_ultimate(&self) -> Option<(f32, f32)> {
+        self.gains.map(|(kp, ki)| (kp / 0.45, 1.2 * kp / ki))
+    }
+}
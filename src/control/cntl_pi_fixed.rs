@@ -0,0 +1,107 @@
+/* Q16.16 fixed-point PI controller for targets without an FPU */
+
+pub fn to_q16(value: f32) -> i32 {
+    let scaled = value * 65536.0;
+    if scaled >= i32::MAX as f32 {
+        i32::MAX
+    } else if scaled <= i32::MIN as f32 {
+        i32::MIN
+    } else if scaled >= 0.0 {
+        (scaled + 0.5) as i32
+    } else {
+        (scaled - 0.5) as i32
+    }
+}
+
+pub fn from_q16(value: i32) -> f32 {
+    value as f32 / 65536.0
+}
+
+/* Q16.16 product with round-to-nearest, saturating at the i64 extremes */
+fn mul_q16(a: i64, b: i64) -> i64 {
+    a.saturating_mul(b).saturating_add(1 << 15) >> 16
+}
+
+fn saturate(value: i64, min: i32, max: i32) -> i64 {
+    if value > max as i64 {
+        max as i64
+    } else if value < min as i64 {
+        min as i64
+    } else {
+        value
+    }
+}
+
+pub struct ControllerPIFixed {
+    kp: i32,         /* Proportional gain, Q16.16 */
+    ki: i32,         /* Integral gain including the sample time, Q16.16 */
+    integrator: i64, /* Integral term, Q16.16, held within the output limits */
+    out_min: i32,    /* Output lower limit, Q16.16 */
+    out_max: i32,    /* Output upper limit, Q16.16 */
+    out: i32,        /* Last output, Q16.16 */
+    enabled: bool,
+    disabled_output: i32, /* Returned by calculate while disabled, Q16.16 */
+}
+
+impl ControllerPIFixed {
+    pub fn with_gains_q16(kp_q16: i32, ki_q16: i32) -> ControllerPIFixed {
+        ControllerPIFixed {
+            kp: kp_q16,
+            ki: ki_q16,
+            integrator: 0,
+            out_min: i32::MIN,
+            out_max: i32::MAX,
+            out: 0,
+            enabled: true,
+            disabled_output: 0,
+        }
+    }
+    pub fn set_gains_q16(&mut self, kp_q16: i32, ki_q16: i32) {
+        self.kp = kp_q16;
+        self.ki = ki_q16;
+    }
+    pub fn set_limits(&mut self, out_min: i32, out_max: i32) {
+        self.out_min = out_min;
+        self.out_max = out_max;
+        self.integrator = saturate(self.integrator, out_min, out_max);
+    }
+    pub fn reset(&mut self) {
+        self.integrator = 0;
+        self.out = 0;
+    }
+    /* While disabled calculate returns the disabled output and the integrator is frozen, so
+    re-enabling resumes from the held integral */
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+    pub fn get_enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn set_disabled_output(&mut self, output_q16: i32) {
+        self.disabled_output = output_q16;
+    }
+    pub fn get_out(&self) -> i32 {
+        self.out
+    }
+    pub fn calculate(&mut self, setpoint: i32, measurement: i32) -> i32 {
+        if !self.enabled {
+            self.out = self.disabled_output;
+            return self.out;
+        }
+        // i64 intermediates with saturating arithmetic, nothing can wrap under sustained error
+        let error = setpoint as i64 - measurement as i64;
+        let p_term = mul_q16(self.kp as i64, error);
+        self.integrator = saturate(
+            self.integrator
+                .saturating_add(mul_q16(self.ki as i64, error)),
+            self.out_min,
+            self.out_max,
+        );
+        self.out = saturate(
+            p_term.saturating_add(self.integrator),
+            self.out_min,
+            self.out_max,
+        ) as i32;
+        self.out
+    }
+}
@@ -0,0 +1,117 @@
+use crate::math::float::{cos, exp, sin};
+use core::f32::consts::TAU;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DqNotchMode {
+    NotchOut, /* Remove the harmonic from d and q */
+    Extract,  /* Output only the harmonic */
+}
+
+/* Complex first-order resonator on x = d + jq,
+y[k] = rho e^(j w T) y[k-1] + g (x[k] - x[k-1]), g = (1 - rho) / (1 - e^(-j w T)).
+Its gain is exactly one at the center frequency, so x - y is a notch with an exact zero, and
+the zero at DC leaves the fundamental in the dq frame untouched. */
+pub struct DqNotch {
+    harmonic_order: u32, /* Harmonic in the stationary frame */
+    center: f32,         /* Signed center frequency in the dq frame, Hz */
+    fundamental: f32,    /* Frame rotation frequency, Hz */
+    sample_time: f32,    /* 1/Frequency of calling process */
+    bandwidth: f32,      /* Approximate -3 dB bandwidth of the resonator, Hz */
+    rho: f32,            /* Pole radius */
+    rot_cos: f32,        /* rho cos(w T) */
+    rot_sin: f32,        /* rho sin(w T) */
+    gain_re: f32,        /* Real part of g */
+    gain_im: f32,        /* Imaginary part of g */
+    x_d: f32,            /* Previous input, d axis */
+    x_q: f32,            /* Previous input, q axis */
+    y_d: f32,            /* Resonator state, d axis */
+    y_q: f32,            /* Resonator state, q axis */
+    mode: DqNotchMode,
+}
+
+impl DqNotch {
+    pub fn new(harmonic_order: u32, fundamental_freq: f32, sample_rate: f32) -> DqNotch {
+        let mut notch = DqNotch {
+            harmonic_order,
+            center: 0.0,
+            fundamental: fundamental_freq,
+            sample_time: 1.0 / sample_rate,
+            bandwidth: 0.1 * fundamental_freq,
+            rho: 0.0,
+            rot_cos: 0.0,
+            rot_sin: 0.0,
+            gain_re: 0.0,
+            gain_im: 0.0,
+            x_d: 0.0,
+            x_q: 0.0,
+            y_d: 0.0,
+            y_q: 0.0,
+            mode: DqNotchMode::NotchOut,
+        };
+        notch.coeff_update();
+        notch
+    }
+    /* Positive-sequence harmonics (7, 13, ...) rotate at (h - 1) f in the dq frame, negative
+    sequence ones (5, 11, ...) at -(h + 1) f, so the 5th and 7th both land at 6 f but with
+    opposite direction and one notch removes only its own harmonic. Triplen orders carry no
+    dq component in a balanced system and are placed as positive sequence. */
+    fn coeff_update(&mut self) {
+        let h = self.harmonic_order as f32;
+        self.center = if self.harmonic_order % 3 == 2 {
+            -(h + 1.0) * self.fundamental
+        } else {
+            (h - 1.0) * self.fundamental
+        };
+        self.rho = exp(-TAU * self.bandwidth * self.sample_time);
+        let phi = TAU * self.center * self.sample_time;
+        self.rot_cos = self.rho * cos(phi);
+        self.rot_sin = self.rho * sin(phi);
+        let denominator = 2.0 - 2.0 * cos(phi);
+        if denominator > 0.0 {
+            self.gain_re = (1.0 - self.rho) * (1.0 - cos(phi)) / denominator;
+            self.gain_im = -(1.0 - self.rho) * sin(phi) / denominator;
+        } else {
+            // A center at DC cannot also have a zero there, the gain is left at zero
+            self.gain_re = 0.0;
+            self.gain_im = 0.0;
+        }
+    }
+    pub fn set_mode(&mut self, mode: DqNotchMode) {
+        self.mode = mode;
+    }
+    pub fn set_bandwidth(&mut self, bandwidth_hz: f32) {
+        self.bandwidth = bandwidth_hz;
+        self.coeff_update();
+    }
+    /* Follows a changing grid frequency */
+    pub fn set_fundamental(&mut self, fundamental_freq: f32) {
+        self.fundamental = fundamental_freq;
+        self.coeff_update();
+    }
+    pub fn get_center_frequency(&self) -> f32 {
+        self.center
+    }
+    pub fn reset(&mut self) {
+        self.y_d = 0.0;
+        self.y_q = 0.0;
+        self.x_d = 0.0;
+        self.x_q = 0.0;
+    }
+    pub fn process(&mut self, d: f32, q: f32) -> (f32, f32) {
+        let (u_d, u_q) = (d - self.x_d, q - self.x_q);
+        self.x_d = d;
+        self.x_q = q;
+        let y_d = self.rot_cos * self.y_d - self.rot_sin * self.y_q + self.gain_re * u_d
+            - self.gain_im * u_q;
+        let y_q = self.rot_sin * self.y_d
+            + self.rot_cos * self.y_q
+            + self.gain_re * u_q
+            + self.gain_im * u_d;
+        self.y_d = y_d;
+        self.y_q = y_q;
+        match self.mode {
+            DqNotchMode::NotchOut => (d - y_d, q - y_q),
+            DqNotchMode::Extract => (y_d, y_q),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+pub struct FoldbackLimiter {
+    max_current: f32,   /* Current limit at or above the knee voltage */
+    min_current: f32,   /* Floor of the folded-back limit, the short-circuit current */
+    knee_voltage: f32,  /* Output voltage below which the limit folds back */
+    slope: f32,         /* Limit reduction in A/V below the knee */
+    recovery: bool,     /* Apply voltage hysteresis before the limit recovers */
+    recovery_band: f32, /* Voltage rise needed before the limit starts to recover */
+    v_eval: f32,        /* Voltage the limit curve is evaluated at */
+    limit: f32,         /* Last current limit */
+}
+
+impl FoldbackLimiter {
+    pub fn new(
+        max_current: f32,
+        foldback_knee_voltage: f32,
+        foldback_slope: f32,
+    ) -> FoldbackLimiter {
+        FoldbackLimiter {
+            max_current,
+            min_current: 0.0,
+            knee_voltage: foldback_knee_voltage,
+            slope: foldback_slope,
+            recovery: false,
+            recovery_band: 0.05 * foldback_knee_voltage,
+            v_eval: f32::INFINITY,
+            limit: max_current,
+        }
+    }
+    pub fn set_min_current(&mut self, min_current: f32) {
+        self.min_current = min_current;
+    }
+    /* With recovery enabled a falling voltage folds the limit back at once, a rising voltage
+    only raises it again after climbing recovery_band above its lowest point. The limit stays
+    continuous since it follows the curve at the held voltage. */
+    pub fn set_recovery(&mut self, enable: bool) {
+        self.recovery = enable;
+    }
+    pub fn set_recovery_band(&mut self, volts: f32) {
+        self.recovery_band = volts;
+    }
+    /* Continuous limit curve, flat above the knee and linear below it down to the floor. A NaN
+    voltage, e.g. a bad sensor read, is treated as fully folded back. */
+    fn curve(&self, voltage: f32) -> f32 {
+        if voltage.is_nan() {
+            return self.min_current;
+        }
+        if voltage >= self.knee_voltage {
+            return self.max_current;
+        }
+        let limit = self.max_current - self.slope * (self.knee_voltage - voltage);
+        if limit < self.min_current {
+            self.min_current
+        } else {
+            limit
+        }
+    }
+    pub fn limit(&mut self, current_ref: f32, output_voltage: f32) -> f32 {
+        self.v_eval = if !self.recovery || output_voltage < self.v_eval {
+            output_voltage
+        } else if output_voltage > self.v_eval + self.recovery_band {
+            output_voltage - self.recovery_band
+        } else {
+            self.v_eval
+        };
+        self.limit = self.curve(self.v_eval);
+        // Plain comparisons, a negative floor or NaN limit must not panic in a protection path
+        let bound = self.limit.max(0.0);
+        if current_ref > bound {
+            bound
+        } else if current_ref < -bound {
+            -bound
+        } else {
+            current_ref
+        }
+    }
+    pub fn get_limit(&self) -> f32 {
+        self.limit
+    }
+    pub fn reset(&mut self) {
+        self.v_eval = f32::INFINITY;
+        self.limit = self.max_current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nan_voltage_folds_back_without_panicking() {
+        let mut limiter = FoldbackLimiter::new(10.0, 20.0, 0.5);
+        limiter.set_min_current(2.0);
+        assert_eq!(limiter.limit(3.0, f32::NAN), 2.0);
+        assert_eq!(limiter.limit(-3.0, f32::NAN), -2.0);
+        assert_eq!(limiter.limit(3.0, 24.0), 3.0);
+
+        limiter.set_recovery(true);
+        limiter.limit(3.0, 5.0);
+        assert_eq!(limiter.limit(3.0, f32::NAN), 2.5);
+    }
+
+    #[test]
+    fn negative_floor_limits_to_zero() {
+        let mut limiter = FoldbackLimiter::new(10.0, 20.0, 0.5);
+        limiter.set_min_current(-1.0);
+        assert_eq!(limiter.limit(3.0, 0.0), 0.0);
+        assert_eq!(limiter.limit(3.0, f32::NAN), 0.0);
+    }
+}
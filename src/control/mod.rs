@@ -1 +1,8 @@
+pub mod autotune;
+pub mod cntl_pi_fixed;
+pub mod dq_notch;
+pub mod foldback;
+pub mod nonlinear;
 pub mod pid;
+pub mod slope_comp;
+pub mod watchdog;
@@ -0,0 +1,79 @@
+pub struct Deadband {
+    half_width: f32, /* Half of the total band width */
+}
+
+impl Deadband {
+    pub fn new(width: f32) -> Deadband {
+        Deadband {
+            half_width: 0.5 * width,
+        }
+    }
+    pub fn set_width(&mut self, width: f32) {
+        self.half_width = 0.5 * width;
+    }
+    pub fn get_width(&self) -> f32 {
+        2.0 * self.half_width
+    }
+    /* Zero inside the band, shifted by the band edge outside so the output is continuous */
+    pub fn apply(&self, input: f32) -> f32 {
+        if input > self.half_width {
+            input - self.half_width
+        } else if input < -self.half_width {
+            input + self.half_width
+        } else {
+            0.0
+        }
+    }
+    /* Zero inside the band, input passed through unchanged outside, leaving a gap at the edges */
+    pub fn apply_centered(&self, input: f32) -> f32 {
+        if input > self.half_width || input < -self.half_width {
+            input
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPS: [f32; 3] = [1e-1, 1e-2, 1e-3];
+
+    #[test]
+    fn apply_is_continuous_at_the_band_edges() {
+        let deadband = Deadband::new(2.0);
+        for edge in [1.0f32, -1.0] {
+            assert_eq!(deadband.apply(edge), 0.0);
+            for eps in EPS {
+                // Just inside is zero, just outside is eps away from zero
+                let step = eps * edge.signum();
+                assert_eq!(deadband.apply(edge - step), 0.0);
+                assert!((deadband.apply(edge + step) - step).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn apply_shifts_by_the_half_width_outside_the_band() {
+        let deadband = Deadband::new(2.0);
+        assert_eq!(deadband.apply(3.0), 2.0);
+        assert_eq!(deadband.apply(-3.0), -2.0);
+        assert_eq!(deadband.apply(0.5), 0.0);
+    }
+
+    #[test]
+    fn apply_centered_jumps_by_the_half_width_at_the_edges() {
+        let deadband = Deadband::new(2.0);
+        for edge in [1.0f32, -1.0] {
+            assert_eq!(deadband.apply_centered(edge), 0.0);
+            for eps in EPS {
+                // The output does not shrink towards zero as eps does, it stays near the edge
+                let step = eps * edge.signum();
+                assert_eq!(deadband.apply_centered(edge - step), 0.0);
+                assert_eq!(deadband.apply_centered(edge + step), edge + step);
+            }
+        }
+        assert_eq!(deadband.apply_centered(3.0), 3.0);
+    }
+}
@@ -1,12 +1,69 @@
+use crate::math::float::tanh;
+
+/* Named after the accumulator update: ForwardEuler is the original integral += error * dt
+on the error of the current call and stays the default, Backward uses the error of the
+previous call instead */
+#[derive(Clone, Copy, PartialEq)]
+pub enum IntegrationMethod {
+    ForwardEuler, /* Integrates the current error over the step */
+    Backward,     /* Integrates the previous error over the step */
+    Trapezoidal,  /* Integrates the mean of the previous and current error */
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SaturationShape {
+    Hard,                   /* Clamp at the output limits */
+    SoftTanh { knee: f32 }, /* Linear up to knee from a limit, then tanh compression towards it */
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PiMode {
+    Pi,        /* Proportional and integral paths both active */
+    PonlyHold, /* Integral frozen at its current value, the proportional path keeps acting */
+    Ionly,     /* Proportional term zeroed */
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DerivativeSource {
+    OnError,       /* Derivative of c * setpoint - measurement */
+    OnMeasurement, /* Derivative of -measurement, no kick on setpoint steps */
+}
+
+/* Clamp that tolerates inverted or NaN bounds, the upper bound wins when they cross */
+fn clamp_to(value: f32, min: f32, max: f32) -> f32 {
+    if value > max {
+        max
+    } else if value < min {
+        min
+    } else {
+        value
+    }
+}
+
 pub struct PID {
     kp: f32,
     ki: f32,
     kd: f32,
+    last_d_error: f32,
+    last_setpoint: f32,
     last_position: f32,
     previous_time: f32,
     current_time: f32,
     first_pass: bool,
     cumulative_error: f32,
+    last_error: f32,
+    integration_method: IntegrationMethod,
+    out_min: f32,
+    out_max: f32,
+    i_min: f32,
+    i_max: f32,
+    b: f32, /* Setpoint weight of the proportional term */
+    c: f32, /* Setpoint weight of the derivative term */
+    saturation: SaturationShape,
+    derivative_source: DerivativeSource,
+    enabled: bool,
+    disabled_output: f32, /* Returned by update while disabled */
+    mode: PiMode,
 }
 
 impl PID {
@@ -15,25 +72,158 @@ impl PID {
             kp: kp,
             ki: ki,
             kd: kd,
+            last_d_error: 0.0,
+            last_setpoint: 0.0,
             last_position: 0.0,
             previous_time: 0.0,
             current_time: 0.0,
             first_pass: true,
             cumulative_error: 0.0,
+            last_error: 0.0,
+            integration_method: IntegrationMethod::ForwardEuler,
+            out_min: f32::NEG_INFINITY,
+            out_max: f32::INFINITY,
+            i_min: f32::NEG_INFINITY,
+            i_max: f32::INFINITY,
+            b: 1.0,
+            c: 1.0,
+            saturation: SaturationShape::Hard,
+            derivative_source: DerivativeSource::OnError,
+            enabled: true,
+            disabled_output: 0.0,
+            mode: PiMode::Pi,
+        }
+    }
+    pub fn set_integration_method(&mut self, integration_method: IntegrationMethod) {
+        self.integration_method = integration_method;
+    }
+    /* Bounds on the total output */
+    pub fn set_limits(&mut self, out_min: f32, out_max: f32) {
+        self.out_min = out_min;
+        self.out_max = out_max;
+    }
+    /* Bounds on the integral term alone, independent of the output limits */
+    pub fn set_integrator_limits(&mut self, i_min: f32, i_max: f32) {
+        self.i_min = i_min;
+        self.i_max = i_max;
+    }
+    /* 2-DOF form, P acts on b * setpoint - measurement and D on c * setpoint - measurement,
+    the integral always sees the full error. b = c = 1 is the standard PID. */
+    pub fn set_setpoint_weights(&mut self, b: f32, c: f32) {
+        self.b = b;
+        self.c = c;
+    }
+    /* The derivative history is rebuilt for the new source so switching causes no kick */
+    pub fn set_derivative_source(&mut self, source: DerivativeSource) {
+        self.derivative_source = source;
+        self.last_d_error = self.derivative_error(self.last_setpoint, self.last_position);
+    }
+    fn derivative_error(&self, setpoint: f32, current_position: f32) -> f32 {
+        match self.derivative_source {
+            DerivativeSource::OnError => self.c * setpoint - current_position,
+            DerivativeSource::OnMeasurement => -current_position,
+        }
+    }
+    /* While disabled update returns the disabled output and the integral is frozen, the
+    timing and derivative history keep following the inputs so re-enabling resumes from the
+    held integral without a time step or derivative kick */
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+    pub fn get_enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn set_disabled_output(&mut self, output: f32) {
+        self.disabled_output = output;
+    }
+    /* Selects which of the P and I paths act, the derivative term is unaffected. The integral
+    is rebased by the change in the proportional term at the last inputs so the output does
+    not jump on a switch. */
+    pub fn set_mode(&mut self, mode: PiMode) {
+        if mode != self.mode && self.ki != 0.0 {
+            let p_before = self.proportional_term(self.last_setpoint, self.last_position);
+            self.mode = mode;
+            let p_after = self.proportional_term(self.last_setpoint, self.last_position);
+            self.cumulative_error += (p_before - p_after) / self.ki;
+        }
+        self.mode = mode;
+    }
+    pub fn get_mode(&self) -> PiMode {
+        self.mode
+    }
+    fn proportional_term(&self, setpoint: f32, current_position: f32) -> f32 {
+        match self.mode {
+            PiMode::Ionly => 0.0,
+            _ => self.kp * (self.b * setpoint - current_position),
+        }
+    }
+    /* How the output approaches the limits set by set_limits, the soft shape never reaches them */
+    pub fn set_saturation_shape(&mut self, shape: SaturationShape) {
+        self.saturation = shape;
+    }
+    fn saturate(&self, output: f32) -> f32 {
+        let knee = match self.saturation {
+            SaturationShape::SoftTanh { knee } if knee > 0.0 && self.out_max > self.out_min => {
+                knee.min(0.5 * (self.out_max - self.out_min))
+            }
+            _ => return clamp_to(output, self.out_min, self.out_max),
+        };
+        // Continuous with unit slope at the knee, out = edge + knee * tanh((x - edge) / knee)
+        let upper = self.out_max - knee;
+        let lower = self.out_min + knee;
+        if output > upper {
+            upper + knee * tanh((output - upper) / knee)
+        } else if output < lower {
+            lower + knee * tanh((output - lower) / knee)
+        } else {
+            output
         }
     }
+    /* Moves the time base by offset, for callers that wrap a sample clock to keep f32
+    resolution on long runs */
+    pub fn shift_time(&mut self, offset: f32) {
+        self.previous_time += offset;
+        self.current_time += offset;
+    }
     pub fn update(&mut self, setpoint: f32, current_position: f32, current_time: f32) -> f32 {
+        if !self.enabled {
+            self.current_time = current_time;
+            self.previous_time = current_time;
+            self.last_error = setpoint - current_position;
+            self.last_d_error = self.derivative_error(setpoint, current_position);
+            self.last_setpoint = setpoint;
+            self.last_position = current_position;
+            return self.disabled_output;
+        }
         self.current_time = current_time;
         let delta_time = self.current_time - self.previous_time;
         let error = setpoint - current_position;
-        self.cumulative_error += error * delta_time;
-        let delta_position = current_position - self.last_position;
+        self.cumulative_error += match self.integration_method {
+            _ if self.mode == PiMode::PonlyHold => 0.0,
+            IntegrationMethod::ForwardEuler => error * delta_time,
+            IntegrationMethod::Backward => self.last_error * delta_time,
+            IntegrationMethod::Trapezoidal => 0.5 * (error + self.last_error) * delta_time,
+        };
+        self.last_error = error;
+        let d_error = self.derivative_error(setpoint, current_position);
+        let delta_d_error = d_error - self.last_d_error;
+        self.last_d_error = d_error;
+        self.last_setpoint = setpoint;
         self.last_position = current_position;
         self.previous_time = self.current_time;
-        let p_term = self.kp * error;
-        let i_term = self.ki * self.cumulative_error;
-        let d_term = self.kd * delta_position / delta_time;
-        let output = p_term + i_term + d_term;
+        let p_term = self.proportional_term(setpoint, current_position);
+        let mut i_term = self.ki * self.cumulative_error;
+        if i_term > self.i_max || i_term < self.i_min {
+            // Hold the accumulated error at the bound so the integral cannot wind up past it
+            i_term = clamp_to(i_term, self.i_min, self.i_max);
+            if self.ki != 0.0 {
+                self.cumulative_error = i_term / self.ki;
+            }
+        }
+        // Damps on -measurement as well as the weighted setpoint, kd > 0 opposes a rising
+        // measurement. The original +kd * d(measurement)/dt pushed along with it.
+        let d_term = self.kd * delta_d_error / delta_time;
+        let output = self.saturate(p_term + i_term + d_term);
         if self.first_pass {
             self.first_pass = false;
             output
@@ -42,3 +232,73 @@ impl PID {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* Output right after a unit setpoint step with the measurement held at zero */
+    fn step_output(c: f32) -> f32 {
+        let mut pid = PID::new(1.0, 0.5, 0.1);
+        pid.set_setpoint_weights(1.0, c);
+        pid.update(0.0, 0.0, 0.01);
+        pid.update(1.0, 0.0, 0.02)
+    }
+
+    #[test]
+    fn derivative_weight_zero_removes_the_setpoint_kick() {
+        let classic = step_output(1.0);
+        let weighted = step_output(0.0);
+        // kd * step / dt = 10 appears only with c = 1, P and I are identical in both
+        assert!((classic - weighted - 10.0).abs() < 1e-3);
+        assert!(weighted < 0.1 * classic);
+    }
+
+    #[test]
+    fn derivative_weight_does_not_change_the_measurement_response() {
+        let mut classic = PID::new(1.0, 0.5, 0.1);
+        let mut weighted = PID::new(1.0, 0.5, 0.1);
+        weighted.set_setpoint_weights(1.0, 0.0);
+        classic.update(1.0, 0.0, 0.01);
+        weighted.update(1.0, 0.0, 0.01);
+        for step in 2..10 {
+            let position = 0.1 * step as f32;
+            let time = 0.01 * step as f32;
+            let a = classic.update(1.0, position, time);
+            let b = weighted.update(1.0, position, time);
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn mode_switches_do_not_bump_the_output() {
+        let mut pid = PID::new(2.0, 1.0, 0.0);
+        let mut last = pid.update(1.0, 0.3, 0.01);
+        for (step, mode) in [PiMode::Ionly, PiMode::PonlyHold, PiMode::Pi]
+            .iter()
+            .enumerate()
+        {
+            pid.set_mode(*mode);
+            let output = pid.update(1.0, 0.3, 0.02 * (step + 1) as f32);
+            // At most one integration step of ki * error * dt apart
+            assert!((output - last).abs() < 0.02);
+            last = output;
+        }
+    }
+
+    #[test]
+    fn inverted_limits_do_not_panic() {
+        let mut pid = PID::new(1.0, 1.0, 0.0);
+        pid.set_limits(1.0, -1.0);
+        pid.set_integrator_limits(1.0, -1.0);
+        pid.update(5.0, 0.0, 0.01);
+        let output = pid.update(5.0, 0.0, 0.02);
+        assert_eq!(output, -1.0);
+
+        pid.set_saturation_shape(SaturationShape::SoftTanh { knee: 0.5 });
+        assert!(pid.update(5.0, 0.0, 0.03).is_finite());
+        // NaN bounds never compare, the output passes through unclamped
+        pid.set_limits(f32::NAN, f32::NAN);
+        assert!(pid.update(5.0, 0.0, 0.04).is_finite());
+    }
+}
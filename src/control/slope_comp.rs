@@ -0,0 +1,50 @@
+pub struct SlopeCompensation {
+    slope: f32,            /* Compensating ramp slope in A/s */
+    switching_period: f32, /* Switching period in seconds */
+    sample_time: f32,      /* 1/Frequency of calling update */
+    time_in_period: f32,   /* Time elapsed since the start of the current switching period */
+}
+
+impl SlopeCompensation {
+    pub fn new(slope: f32, switching_period: f32, sample_rate: f32) -> SlopeCompensation {
+        SlopeCompensation {
+            slope,
+            switching_period,
+            sample_time: 1.0 / sample_rate,
+            time_in_period: 0.0,
+        }
+    }
+    pub fn set_slope(&mut self, slope: f32) {
+        self.slope = slope;
+    }
+    pub fn get_slope(&self) -> f32 {
+        self.slope
+    }
+    /* Minimum slope for a buck stage: ma > (m2 - m1) / 2 with m1 = (Vin - Vout)/L, m2 = Vout/L */
+    pub fn recommended_slope(inductance: f32, v_out: f32, v_in: f32) -> f32 {
+        let slope = (2.0 * v_out - v_in) / (2.0 * inductance);
+        if slope > 0.0 {
+            slope
+        } else {
+            0.0
+        }
+    }
+    pub fn compensate(&self, sensed_current: f32, time_in_period: f32) -> f32 {
+        let mut t = time_in_period % self.switching_period;
+        if t < 0.0 {
+            t += self.switching_period;
+        }
+        sensed_current - self.slope * t
+    }
+    pub fn start_period(&mut self) {
+        self.time_in_period = 0.0;
+    }
+    pub fn update(&mut self, sensed_current: f32) -> f32 {
+        let out = self.compensate(sensed_current, self.time_in_period);
+        self.time_in_period += self.sample_time;
+        if self.time_in_period >= self.switching_period {
+            self.time_in_period -= self.switching_period;
+        }
+        out
+    }
+}
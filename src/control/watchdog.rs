@@ -0,0 +1,71 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoopHealth {
+    Ok,      /* Interval within the jitter tolerance */
+    Jitter,  /* Interval off by more than the tolerance but not an overrun */
+    Overrun, /* Interval long enough that at least one execution was missed or late */
+}
+
+pub struct LoopWatchdog {
+    expected_period: f32, /* Nominal time between calls in seconds */
+    tolerance: f32,       /* Accepted deviation as a fraction of the period */
+    overrun_ratio: f32,   /* Interval in periods beyond which a call counts as overrun */
+    overruns: u32,        /* Overruns since reset, saturating */
+    jitter_events: u32,   /* Jitter reports since reset, saturating */
+    max_interval: f32,    /* Longest interval seen since reset */
+    health: LoopHealth,   /* Result of the last tick */
+}
+
+impl LoopWatchdog {
+    pub fn new(expected_period_s: f32, tolerance: f32) -> LoopWatchdog {
+        LoopWatchdog {
+            expected_period: expected_period_s,
+            tolerance: tolerance.abs(),
+            overrun_ratio: 1.5,
+            overruns: 0,
+            jitter_events: 0,
+            max_interval: 0.0,
+            health: LoopHealth::Ok,
+        }
+    }
+    pub fn set_jitter_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance.abs();
+    }
+    /* Defaults to 1.5 periods, halfway to the next scheduled execution */
+    pub fn set_overrun_threshold(&mut self, periods: f32) {
+        self.overrun_ratio = periods;
+    }
+    pub fn tick(&mut self, actual_dt_s: f32) -> LoopHealth {
+        if actual_dt_s > self.max_interval {
+            self.max_interval = actual_dt_s;
+        }
+        let deviation = (actual_dt_s - self.expected_period).abs();
+        self.health = if actual_dt_s > self.overrun_ratio * self.expected_period {
+            self.overruns = self.overruns.saturating_add(1);
+            LoopHealth::Overrun
+        } else if deviation > self.tolerance * self.expected_period {
+            self.jitter_events = self.jitter_events.saturating_add(1);
+            LoopHealth::Jitter
+        } else {
+            LoopHealth::Ok
+        };
+        self.health
+    }
+    pub fn get_health(&self) -> LoopHealth {
+        self.health
+    }
+    pub fn get_overrun_count(&self) -> u32 {
+        self.overruns
+    }
+    pub fn get_jitter_count(&self) -> u32 {
+        self.jitter_events
+    }
+    pub fn get_max_interval(&self) -> f32 {
+        self.max_interval
+    }
+    pub fn reset(&mut self) {
+        self.overruns = 0;
+        self.jitter_events = 0;
+        self.max_interval = 0.0;
+        self.health = LoopHealth::Ok;
+    }
+}
@@ -1,7 +1,12 @@
 #![no_std]
 
+pub mod battery;
 pub mod control;
+pub mod math;
+pub mod modulation;
+pub mod motor_control;
 pub mod mppt;
 pub mod phase_locked_loop;
 pub mod signal;
+pub mod system;
 pub mod transform;
@@ -0,0 +1,151 @@
+use core::f32::consts::{FRAC_PI_2, LN_2, LOG2_E, PI, SQRT_2, TAU};
+
+/* Wraps an angle into [-PI, PI] */
+pub fn wrap_pi(x: f32) -> f32 {
+    let mut x = x % TAU;
+    if x > PI {
+        x -= TAU;
+    } else if x < -PI {
+        x += TAU;
+    }
+    x
+}
+
+/* Wraps an angle into [0, 2*PI) */
+pub fn wrap_2pi(x: f32) -> f32 {
+    let mut x = x % TAU;
+    if x < 0.0 {
+        x += TAU;
+    }
+    if x >= TAU {
+        x -= TAU;
+    }
+    x
+}
+
+pub fn sin(x: f32) -> f32 {
+    let mut x = wrap_pi(x);
+    if x > FRAC_PI_2 {
+        x = PI - x;
+    } else if x < -FRAC_PI_2 {
+        x = -PI - x;
+    }
+    // Taylor series up to x^11, error below 1e-7 on [-PI/2, PI/2]
+    let x2 = x * x;
+    x * (1.0
+        + x2 * (-1.0 / 6.0
+            + x2 * (1.0 / 120.0
+                + x2 * (-1.0 / 5040.0 + x2 * (1.0 / 362880.0 + x2 * (-1.0 / 39916800.0))))))
+}
+
+pub fn cos(x: f32) -> f32 {
+    sin(x + FRAC_PI_2)
+}
+
+pub fn sqrt(x: f32) -> f32 {
+    if x <= 0.0 {
+        return if x == 0.0 { 0.0 } else { f32::NAN };
+    }
+    // Bit-level initial guess refined with Newton-Raphson
+    let mut y = f32::from_bits((x.to_bits() >> 1) + 0x1fbd_1df5);
+    y = 0.5 * (y + x / y);
+    y = 0.5 * (y + x / y);
+    0.5 * (y + x / y)
+}
+
+pub fn atan(x: f32) -> f32 {
+    let (z, invert) = if x.abs() > 1.0 {
+        (1.0 / x, true)
+    } else {
+        (x, false)
+    };
+    let z2 = z * z;
+    let a = z
+        * (0.999_977_26
+            + z2 * (-0.332_623_47
+                + z2 * (0.193_543_46
+                    + z2 * (-0.116_432_87 + z2 * (0.052_653_32 + z2 * -0.011_721_2)))));
+    if invert {
+        FRAC_PI_2.copysign(x) - a
+    } else {
+        a
+    }
+}
+
+pub fn atan2(y: f32, x: f32) -> f32 {
+    if x > 0.0 {
+        atan(y / x)
+    } else if x < 0.0 {
+        atan(y / x) + PI.copysign(y)
+    } else if y != 0.0 {
+        FRAC_PI_2.copysign(y)
+    } else {
+        0.0
+    }
+}
+
+pub fn exp(x: f32) -> f32 {
+    if x > 88.73 {
+        return f32::INFINITY;
+    }
+    if x < -103.98 {
+        return 0.0;
+    }
+    // exp(x) = 2^k * exp(r) with |r| <= ln(2)/2
+    let k = (x * LOG2_E + if x < 0.0 { -0.5 } else { 0.5 }) as i32;
+    // ln(2) split in a high part exact in f32 and a low correction (Cody-Waite)
+    let r = (x - k as f32 * 0.693_145_75) - k as f32 * 1.428_606_8e-6;
+    let p = 1.0
+        + r * (1.0
+            + r * (0.5
+                + r * (1.0 / 6.0 + r * (1.0 / 24.0 + r * (1.0 / 120.0 + r * (1.0 / 720.0))))));
+    // Scale in two halves so 2^k stays a normal number over the whole range
+    let k1 = k / 2;
+    let k2 = k - k1;
+    p * f32::from_bits(((k1 + 127) as u32) << 23) * f32::from_bits(((k2 + 127) as u32) << 23)
+}
+
+pub fn ln(x: f32) -> f32 {
+    if x.is_nan() || x < 0.0 {
+        return f32::NAN;
+    }
+    if x == 0.0 {
+        return f32::NEG_INFINITY;
+    }
+    if x.is_infinite() {
+        return x;
+    }
+    // Normalize subnormals before splitting off the exponent
+    let (x, bias) = if x < f32::MIN_POSITIVE {
+        (x * 8_388_608.0, -23)
+    } else {
+        (x, 0)
+    };
+    // ln(x) = e * ln(2) + ln(m) with m in [sqrt(2)/2, sqrt(2))
+    let bits = x.to_bits();
+    let mut e = ((bits >> 23) & 0xff) as i32 - 127 + bias;
+    let mut m = f32::from_bits((bits & 0x007f_ffff) | 0x3f80_0000);
+    if m > SQRT_2 {
+        m *= 0.5;
+        e += 1;
+    }
+    // ln(m) = 2 atanh(s), s = (m - 1) / (m + 1), |s| < 0.172
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let l = 2.0 * s * (1.0 + s2 * (1.0 / 3.0 + s2 * (1.0 / 5.0 + s2 * (1.0 / 7.0 + s2 / 9.0))));
+    e as f32 * LN_2 + l
+}
+
+pub fn tanh(x: f32) -> f32 {
+    // Saturated beyond |x| = 9 where tanh rounds to 1 in f32
+    if x > 9.0 {
+        return 1.0;
+    }
+    if x < -9.0 {
+        return -1.0;
+    }
+    if x.abs() < 0.01 {
+        return x * (1.0 - x * x / 3.0);
+    }
+    1.0 - 2.0 / (exp(2.0 * x) + 1.0)
+}
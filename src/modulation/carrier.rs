@@ -0,0 +1,75 @@
+#[derive(Clone, Copy, PartialEq)]
+pub enum CarrierType {
+    Sawtooth,   /* Edge-aligned, ramps 0 -> 1 and resets */
+    Triangular, /* Center-aligned, 1 -> 0 -> 1, pulse centered in the period */
+}
+
+pub struct CarrierComparator {
+    carrier_type: CarrierType,
+    carrier_freq: f32, /* Carrier (switching) frequency */
+    sample_rate: f32,  /* Frequency of calling update */
+    phase: f32,        /* Normalized position within the carrier period, 0..1 */
+    carrier: f32,      /* Carrier value used for the last comparison, 0..1 */
+    dead_time: f32,    /* Dead-time between complementary outputs in seconds */
+}
+
+impl CarrierComparator {
+    pub fn new(carrier_freq: f32, sample_rate: f32) -> CarrierComparator {
+        CarrierComparator {
+            carrier_type: CarrierType::Sawtooth,
+            carrier_freq,
+            sample_rate,
+            phase: 0.0,
+            carrier: 0.0,
+            dead_time: 0.0,
+        }
+    }
+    pub fn set_carrier_type(&mut self, carrier_type: CarrierType) {
+        self.carrier_type = carrier_type;
+    }
+    pub fn set_dead_time(&mut self, dead_time: f32) {
+        self.dead_time = dead_time;
+    }
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase - (phase as i32) as f32;
+        if self.phase < 0.0 {
+            self.phase += 1.0;
+        }
+    }
+    pub fn get_phase(&self) -> f32 {
+        self.phase
+    }
+    pub fn get_carrier_value(&self) -> f32 {
+        self.carrier
+    }
+    pub fn update(&mut self, duty: f32) -> bool {
+        self.advance();
+        duty > self.carrier
+    }
+    pub fn update_complementary(&mut self, duty: f32) -> (bool, bool) {
+        self.advance();
+        // Half of the dead band expressed in carrier units, placed on both sides of every edge
+        let h = 0.5 * self.dead_time * self.slope();
+        let c = self.carrier;
+        match self.carrier_type {
+            CarrierType::Sawtooth => (c >= h && c < duty - h, c >= duty + h && c < 1.0 - h),
+            CarrierType::Triangular => (c < duty - h, c >= duty + h),
+        }
+    }
+    fn slope(&self) -> f32 {
+        match self.carrier_type {
+            CarrierType::Sawtooth => self.carrier_freq,
+            CarrierType::Triangular => 2.0 * self.carrier_freq,
+        }
+    }
+    fn advance(&mut self) {
+        self.carrier = match self.carrier_type {
+            CarrierType::Sawtooth => self.phase,
+            CarrierType::Triangular => (2.0 * self.phase - 1.0).abs(),
+        };
+        self.phase += self.carrier_freq / self.sample_rate;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+    }
+}
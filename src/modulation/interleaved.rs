@@ -0,0 +1,39 @@
+use crate::modulation::carrier::{CarrierComparator, CarrierType};
+
+pub struct InterleavedPWM<const N: usize> {
+    carriers: [CarrierComparator; N], /* Carrier k starts k/N of a period later than carrier 0 */
+}
+
+impl<const N: usize> InterleavedPWM<N> {
+    pub fn new(carrier_freq: f32, sample_rate: f32) -> InterleavedPWM<N> {
+        InterleavedPWM {
+            carriers: core::array::from_fn(|k| {
+                let mut carrier = CarrierComparator::new(carrier_freq, sample_rate);
+                carrier.set_carrier_type(CarrierType::Triangular);
+                carrier.set_phase(1.0 - k as f32 / N as f32);
+                carrier
+            }),
+        }
+    }
+    pub fn set_carrier_type(&mut self, carrier_type: CarrierType) {
+        for carrier in self.carriers.iter_mut() {
+            carrier.set_carrier_type(carrier_type);
+        }
+    }
+    pub fn get_carrier_phase(&self, index: usize) -> f32 {
+        self.carriers[index].get_phase()
+    }
+    pub fn get_carrier_value(&self, index: usize) -> f32 {
+        self.carriers[index].get_carrier_value()
+    }
+    pub fn update(&mut self, duty: f32) -> [bool; N] {
+        self.update_multi([duty; N])
+    }
+    pub fn update_multi(&mut self, duties: [f32; N]) -> [bool; N] {
+        let mut gates = [false; N];
+        for (k, carrier) in self.carriers.iter_mut().enumerate() {
+            gates[k] = carrier.update(duties[k]);
+        }
+        gates
+    }
+}
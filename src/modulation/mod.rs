@@ -0,0 +1,3 @@
+pub mod carrier;
+pub mod interleaved;
+pub mod timer_adapter;
@@ -0,0 +1,64 @@
+/* Channels are taken as active while the counter is below the compare value (PWM mode 1) */
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimerAlignment {
+    EdgeAligned,   /* Counts 0 -> period - 1 and wraps, one PWM cycle is period ticks */
+    CenterAligned, /* Counts 0 -> period -> 0, one PWM cycle is 2 * period ticks */
+}
+
+pub struct TimerAdapter {
+    alignment: TimerAlignment,
+    period: u32,    /* Auto-reload span in ticks as described by the alignment */
+    dead_time: u32, /* Dead-time between complementary channels in ticks */
+}
+
+impl TimerAdapter {
+    pub fn new(alignment: TimerAlignment, period: u32) -> TimerAdapter {
+        TimerAdapter {
+            alignment,
+            period,
+            dead_time: 0,
+        }
+    }
+    pub fn set_alignment(&mut self, alignment: TimerAlignment) {
+        self.alignment = alignment;
+    }
+    pub fn set_period(&mut self, ticks: u32) {
+        self.period = ticks;
+    }
+    pub fn get_period(&self) -> u32 {
+        self.period
+    }
+    pub fn set_dead_time(&mut self, ticks: u32) {
+        self.dead_time = ticks;
+    }
+    /* Timer ticks in one PWM cycle */
+    pub fn get_cycle_ticks(&self) -> u32 {
+        match self.alignment {
+            TimerAlignment::EdgeAligned => self.period,
+            TimerAlignment::CenterAligned => 2 * self.period,
+        }
+    }
+    /* Compare value for a duty in [0, 1], rounded to the nearest tick. Center-aligned the
+    pulse spans both slopes around the counter valley and is symmetric about it. */
+    pub fn compare(&self, duty: f32) -> u32 {
+        let duty = duty.clamp(0.0, 1.0);
+        ((duty * self.period as f32 + 0.5) as u32).min(self.period)
+    }
+    /* (high, low) compare values, the high-side channel is active below high and the inverted
+    low-side channel at or above low, with low - high equal to the dead-time. Center-aligned
+    the dead-time splits evenly around the nominal edge on both slopes. Edge-aligned only the
+    compare edge gets the dead-time, the edge at the counter wrap needs the timer's own
+    dead-time insertion. */
+    pub fn complementary(&self, duty: f32) -> (u32, u32) {
+        let duty = duty.clamp(0.0, 1.0);
+        let dead_time = self.dead_time.min(self.period);
+        let edge = duty * self.period as f32 - 0.5 * dead_time as f32;
+        let high = if edge > 0.0 {
+            ((edge + 0.5) as u32).min(self.period - dead_time)
+        } else {
+            0
+        };
+        (high, high + dead_time)
+    }
+}
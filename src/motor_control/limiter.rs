@@ -0,0 +1,90 @@
+use crate::math::float::sqrt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotorParams {
+    pub ld: f32,   /* d-axis inductance in henries */
+    pub lq: f32,   /* q-axis inductance in henries */
+    pub flux: f32, /* Permanent magnet flux linkage in webers */
+}
+
+/* Keeps the dq current reference inside the current circle id^2 + iq^2 <= i_max^2 and the
+steady-state voltage ellipse (w lq iq)^2 + (w (ld id + flux))^2 <= v^2. The stator resistance
+drop is neglected, leave a margin for it in available_voltage. */
+pub struct CurrentReferenceLimiter {
+    i_max: f32,            /* Current circle radius in amps */
+    current_limited: bool, /* Last reference was reduced by the current circle */
+    voltage_limited: bool, /* Last reference was reduced by the voltage ellipse */
+}
+
+impl CurrentReferenceLimiter {
+    pub fn new(i_max: f32) -> CurrentReferenceLimiter {
+        CurrentReferenceLimiter {
+            i_max: i_max.abs(),
+            current_limited: false,
+            voltage_limited: false,
+        }
+    }
+    pub fn set_i_max(&mut self, i_max: f32) {
+        self.i_max = i_max.abs();
+    }
+    /* speed is electrical in rad/s. Over the current circle id is reduced first so torque is
+    kept, over the voltage ellipse iq is reduced first so the field weakening id is kept, and
+    only if iq = 0 is still infeasible does id move towards -flux / ld. Each step clamps one
+    axis continuously, so the result is continuous in every input. */
+    pub fn limit(
+        &mut self,
+        id_ref: f32,
+        iq_ref: f32,
+        available_voltage: f32,
+        speed: f32,
+        motor_params: MotorParams,
+    ) -> (f32, f32) {
+        let i_max = self.i_max;
+        let mut id = id_ref;
+        let mut iq = clamp_symmetric(iq_ref, i_max);
+        let id_room = sqrt(i_max * i_max - iq * iq);
+        id = clamp_symmetric(id, id_room);
+        self.current_limited = id != id_ref || iq != iq_ref;
+
+        self.voltage_limited = false;
+        let w = speed.abs();
+        if w > 0.0 && motor_params.lq > 0.0 {
+            let v_limit = available_voltage.max(0.0) / w;
+            // Flux linkage budget left for the q axis once the d axis flux is set
+            let psi_d = motor_params.ld * id + motor_params.flux;
+            let psi_q_room = v_limit * v_limit - psi_d * psi_d;
+            if psi_q_room >= 0.0 {
+                let iq_room = sqrt(psi_q_room) / motor_params.lq;
+                if iq.abs() > iq_room {
+                    iq = clamp_symmetric(iq, iq_room);
+                    self.voltage_limited = true;
+                }
+            } else {
+                // Even without torque current the d axis flux is too high, weaken further
+                iq = 0.0;
+                if motor_params.ld > 0.0 {
+                    let id_target = (v_limit.copysign(psi_d) - motor_params.flux) / motor_params.ld;
+                    id = clamp_symmetric(id_target, i_max);
+                }
+                self.voltage_limited = true;
+            }
+        }
+        (id, iq)
+    }
+    pub fn is_current_limited(&self) -> bool {
+        self.current_limited
+    }
+    pub fn is_voltage_limited(&self) -> bool {
+        self.voltage_limited
+    }
+}
+
+fn clamp_symmetric(value: f32, limit: f32) -> f32 {
+    if value > limit {
+        limit
+    } else if value < -limit {
+        -limit
+    } else {
+        value
+    }
+}
@@ -0,0 +1,4 @@
+pub mod limiter;
+pub mod sensor_check;
+pub mod sixstep;
+pub mod vf;
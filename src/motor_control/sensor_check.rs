@@ -0,0 +1,60 @@
+/* Without a neutral the three phase currents sum to zero, a persistent residual points to a
+current sensor fault (offset, gain or open channel) or a ground fault */
+pub struct PhaseSumMonitor {
+    threshold: f32, /* Residual magnitude in amps that counts as a violation */
+    debounce: u32,  /* Consecutive violations needed to trip */
+    count: u32,     /* Consecutive violations so far */
+    residual: f32,  /* Last ia + ib + ic */
+    fault: bool,    /* Latched until reset */
+}
+
+impl PhaseSumMonitor {
+    /* Never trips until a threshold is set */
+    pub fn new() -> PhaseSumMonitor {
+        PhaseSumMonitor {
+            threshold: f32::INFINITY,
+            debounce: 3,
+            count: 0,
+            residual: 0.0,
+            fault: false,
+        }
+    }
+    pub fn set_threshold(&mut self, amps: f32) {
+        self.threshold = amps.abs();
+    }
+    /* Samples in a row above the threshold before tripping, short transients such as
+    switching spikes or sampling skew between phases stay below it */
+    pub fn set_debounce(&mut self, samples: u32) {
+        self.debounce = if samples < 1 { 1 } else { samples };
+    }
+    /* Returns true once tripped, the fault stays latched until reset */
+    pub fn check(&mut self, ia: f32, ib: f32, ic: f32) -> bool {
+        self.residual = ia + ib + ic;
+        // A NaN residual from a dead channel counts as a violation
+        if self.residual.abs() > self.threshold || self.residual.is_nan() {
+            self.count = self.count.saturating_add(1);
+            if self.count >= self.debounce {
+                self.fault = true;
+            }
+        } else {
+            self.count = 0;
+        }
+        self.fault
+    }
+    pub fn get_residual(&self) -> f32 {
+        self.residual
+    }
+    pub fn is_fault(&self) -> bool {
+        self.fault
+    }
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.fault = false;
+    }
+}
+
+impl Default for PhaseSumMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,111 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhaseDrive {
+    High,
+    Low,
+    Float,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HallAlignment {
+    Deg0,  /* Hall edges 30 degrees either side of each sector centre */
+    Deg60, /* Hall pattern shifted one sector ahead */
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommutationState {
+    pub a: PhaseDrive,
+    pub b: PhaseDrive,
+    pub c: PhaseDrive,
+    pub sector: Option<u8>, /* 0..5, None for an illegal Hall code */
+}
+
+/* Hall code (a << 2 | b << 1 | c) to sector for forward rotation, the code sequence is
+5, 4, 6, 2, 3, 1. 000 and 111 cannot occur with 120 degree sensors. */
+const HALL_TO_SECTOR: [Option<u8>; 8] = [
+    None,
+    Some(5),
+    Some(3),
+    Some(4),
+    Some(1),
+    Some(0),
+    Some(2),
+    None,
+];
+
+/* Forward (high, low) phase pair per sector, the stator field sits 90 degrees ahead of the
+sector centre. Phases are numbered a = 0, b = 1, c = 2. */
+const FORWARD_PAIRS: [(usize, usize); 6] = [(1, 2), (1, 0), (2, 0), (2, 1), (0, 1), (0, 2)];
+
+pub struct SixStepCommutator {
+    direction: Direction,
+    alignment: HallAlignment,
+    invalid: bool, /* Last Hall code was 000 or 111 */
+    state: CommutationState,
+}
+
+impl Default for SixStepCommutator {
+    fn default() -> Self {
+        SixStepCommutator::new()
+    }
+}
+
+impl SixStepCommutator {
+    pub fn new() -> SixStepCommutator {
+        SixStepCommutator {
+            direction: Direction::Forward,
+            alignment: HallAlignment::Deg0,
+            invalid: false,
+            state: CommutationState {
+                a: PhaseDrive::Float,
+                b: PhaseDrive::Float,
+                c: PhaseDrive::Float,
+                sector: None,
+            },
+        }
+    }
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+    pub fn set_hall_alignment(&mut self, alignment: HallAlignment) {
+        self.alignment = alignment;
+    }
+    /* Illegal Hall codes float every phase and flag a sensor fault */
+    pub fn update(&mut self, hall_a: bool, hall_b: bool, hall_c: bool) -> CommutationState {
+        let code = ((hall_a as usize) << 2) | ((hall_b as usize) << 1) | hall_c as usize;
+        let mut drive = [PhaseDrive::Float; 3];
+        let sector = HALL_TO_SECTOR[code].map(|sector| match self.alignment {
+            HallAlignment::Deg0 => sector,
+            HallAlignment::Deg60 => (sector + 1) % 6,
+        });
+        self.invalid = sector.is_none();
+        if let Some(s) = sector {
+            let (high, low) = FORWARD_PAIRS[s as usize];
+            // Reversing only swaps the pair, which turns the field by 180 degrees
+            let (high, low) = match self.direction {
+                Direction::Forward => (high, low),
+                Direction::Reverse => (low, high),
+            };
+            drive[high] = PhaseDrive::High;
+            drive[low] = PhaseDrive::Low;
+        }
+        self.state = CommutationState {
+            a: drive[0],
+            b: drive[1],
+            c: drive[2],
+            sector,
+        };
+        self.state
+    }
+    pub fn invalid_hall(&self) -> bool {
+        self.invalid
+    }
+    pub fn get_state(&self) -> CommutationState {
+        self.state
+    }
+}
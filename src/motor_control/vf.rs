@@ -0,0 +1,67 @@
+use crate::math::float::wrap_2pi;
+use core::f32::consts::TAU;
+
+pub struct VfController {
+    sample_time: f32,   /* 1/Frequency of calling update */
+    frequency: f32,     /* Commanded electrical frequency, negative reverses rotation */
+    vf_ratio: f32,      /* Volts per hertz of the linear V/f curve */
+    boost_voltage: f32, /* Extra voltage at zero frequency */
+    boost_corner: f32,  /* Frequency at which the boost has tapered to zero */
+    max_voltage: f32,   /* Voltage magnitude ceiling */
+    angle: f32,         /* Electrical angle in [0, 2*PI) */
+    magnitude: f32,     /* Last voltage magnitude */
+}
+
+impl VfController {
+    pub fn new(sample_time: f32) -> VfController {
+        VfController {
+            sample_time,
+            frequency: 0.0,
+            vf_ratio: 0.0,
+            boost_voltage: 0.0,
+            boost_corner: 0.0,
+            max_voltage: f32::INFINITY,
+            angle: 0.0,
+            magnitude: 0.0,
+        }
+    }
+    pub fn set_frequency(&mut self, hz: f32) {
+        self.frequency = hz;
+    }
+    pub fn set_vf_ratio(&mut self, volts_per_hz: f32) {
+        self.vf_ratio = volts_per_hz;
+    }
+    /* Boost fades linearly from boost_voltage at 0 Hz to nothing at corner_freq, so the
+    curve stays continuous where it joins the plain V/f line */
+    pub fn set_boost(&mut self, boost_voltage: f32, corner_freq: f32) {
+        self.boost_voltage = boost_voltage;
+        self.boost_corner = corner_freq;
+    }
+    pub fn set_max_voltage(&mut self, max_voltage: f32) {
+        self.max_voltage = max_voltage;
+    }
+    pub fn reset(&mut self) {
+        self.angle = 0.0;
+        self.magnitude = 0.0;
+    }
+    pub fn update(&mut self) -> (f32, f32) {
+        let f = self.frequency.abs();
+        let mut magnitude = self.vf_ratio * f;
+        if f < self.boost_corner {
+            magnitude += self.boost_voltage * (1.0 - f / self.boost_corner);
+        }
+        self.magnitude = if magnitude > self.max_voltage {
+            self.max_voltage
+        } else {
+            magnitude
+        };
+        self.angle = wrap_2pi(self.angle + TAU * self.frequency * self.sample_time);
+        (self.magnitude, self.angle)
+    }
+    pub fn get_angle(&self) -> f32 {
+        self.angle
+    }
+    pub fn get_magnitude(&self) -> f32 {
+        self.magnitude
+    }
+}
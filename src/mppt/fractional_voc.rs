@@ -0,0 +1,60 @@
+pub struct FractionalVoc {
+    k_factor: f32,         /* Vmp / Voc ratio, typically 0.76 */
+    voc: f32,              /* Last measured open-circuit voltage */
+    voc_valid: bool,       /* A Voc measurement has been recorded */
+    voc_enable: bool,      /* Voc measurement enabled, constant-voltage mode otherwise */
+    v_constant: f32,       /* Reference used in constant-voltage mode */
+    sample_time: f32,      /* 1/Frequency of calling calculate */
+    refresh_interval: f32, /* Time between Voc measurements, 0 measures only once */
+    elapsed: f32,          /* Time since the last Voc measurement */
+}
+
+impl FractionalVoc {
+    pub fn new(k_factor: f32) -> FractionalVoc {
+        FractionalVoc {
+            k_factor,
+            voc: 0.0,
+            voc_valid: false,
+            voc_enable: true,
+            v_constant: 0.0,
+            sample_time: 1.0,
+            refresh_interval: 0.0,
+            elapsed: 0.0,
+        }
+    }
+    pub fn set_sample_time(&mut self, sample_time: f32) {
+        self.sample_time = sample_time;
+    }
+    pub fn set_refresh_interval(&mut self, seconds: f32) {
+        self.refresh_interval = seconds;
+    }
+    pub fn set_constant_voltage(&mut self, v_constant: f32) {
+        self.v_constant = v_constant;
+    }
+    pub fn set_voc_measurement_enabled(&mut self, enable: bool) {
+        self.voc_enable = enable;
+    }
+    /* True when the caller should briefly open the PV circuit and call measure_voc */
+    pub fn is_voc_measurement_due(&self) -> bool {
+        self.voc_enable
+            && (!self.voc_valid
+                || (self.refresh_interval > 0.0 && self.elapsed >= self.refresh_interval))
+    }
+    /* Record the PV voltage sampled while the array is open-circuited */
+    pub fn measure_voc(&mut self, pv_v_open: f32) {
+        self.voc = pv_v_open;
+        self.voc_valid = true;
+        self.elapsed = 0.0;
+    }
+    pub fn get_voc(&self) -> f32 {
+        self.voc
+    }
+    pub fn calculate(&mut self) -> f32 {
+        self.elapsed += self.sample_time;
+        if self.voc_enable && self.voc_valid {
+            self.k_factor * self.voc
+        } else {
+            self.v_constant
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::mppt::mppt::{perturb_and_observe, validate_config, MpptError};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GlobalMpptState {
+    Scanning,
+    Tracking,
+}
+
+pub struct GlobalMppt {
+    v_min: f32,
+    v_max: f32,
+    scan_step: f32,
+    state: GlobalMpptState,
+    v_ref: f32,                         /* Voltage reference handed to the converter */
+    peak_v: f32,                        /* Voltage of the highest power seen during the last scan */
+    peak_power: f32,                    /* Power at peak_v */
+    sample_time: f32,                   /* 1/Frequency of calling calculate */
+    rescan_interval: f32,               /* Time between scans, 0 disables periodic rescans */
+    elapsed: f32,                       /* Time spent tracking since the last scan */
+    tracker: perturb_and_observe::MPPT, /* Fine tracking around the global peak */
+    config_error: Option<MpptError>,    /* Set when the scan window or step is unusable */
+}
+
+impl GlobalMppt {
+    pub fn new(v_min: f32, v_max: f32, scan_step: f32) -> GlobalMppt {
+        let mut tracker = perturb_and_observe::MPPT::new();
+        tracker.set_v_out_limits(v_min, v_max);
+        tracker.set_step_size(0.25 * scan_step);
+        GlobalMppt {
+            v_min,
+            v_max,
+            scan_step,
+            state: GlobalMpptState::Scanning,
+            v_ref: v_min,
+            peak_v: v_min,
+            peak_power: 0.0,
+            sample_time: 1.0,
+            rescan_interval: 0.0,
+            elapsed: 0.0,
+            tracker,
+            config_error: validate_config(v_min, v_max, scan_step).err(),
+        }
+    }
+    /* A non-positive scan step would never reach v_max, calculate holds v_min until fixed */
+    pub fn get_error(&self) -> Option<MpptError> {
+        self.config_error
+    }
+    pub fn set_sample_time(&mut self, sample_time: f32) {
+        self.sample_time = sample_time;
+    }
+    pub fn set_rescan_interval(&mut self, seconds: f32) {
+        self.rescan_interval = seconds;
+    }
+    pub fn set_tracking_step(&mut self, step_size: f32) {
+        self.tracker.set_step_size(step_size);
+    }
+    pub fn start_scan(&mut self) {
+        self.state = GlobalMpptState::Scanning;
+        self.v_ref = self.v_min;
+        self.peak_v = self.v_min;
+        self.peak_power = 0.0;
+    }
+    pub fn get_state(&self) -> GlobalMpptState {
+        self.state
+    }
+    pub fn get_global_peak_voltage(&self) -> f32 {
+        self.peak_v
+    }
+    pub fn get_global_peak_power(&self) -> f32 {
+        self.peak_power
+    }
+    pub fn calculate(&mut self, pv_i: f32, pv_v: f32) -> f32 {
+        if self.config_error.is_some() {
+            return self.v_ref;
+        }
+        match self.state {
+            GlobalMpptState::Scanning => {
+                // The measurement belongs to the reference issued on the previous call
+                let power = pv_i * pv_v;
+                if power > self.peak_power {
+                    self.peak_power = power;
+                    self.peak_v = self.v_ref;
+                }
+                if self.v_ref + self.scan_step > self.v_max {
+                    self.state = GlobalMpptState::Tracking;
+                    self.elapsed = 0.0;
+                    // Restart P&O from the peak so no direction or power from before the
+                    // scan carries over into the first decision
+                    self.tracker.reset_to(self.peak_v);
+                    self.v_ref = self.peak_v;
+                } else {
+                    self.v_ref += self.scan_step;
+                }
+            }
+            GlobalMpptState::Tracking => {
+                self.tracker.calculate(pv_i, pv_v);
+                self.v_ref = self.tracker.get_mppt_v_out();
+                self.elapsed += self.sample_time;
+                if self.rescan_interval > 0.0 && self.elapsed >= self.rescan_interval {
+                    self.start_scan();
+                }
+            }
+        }
+        self.v_ref
+    }
+}
@@ -1 +1,3 @@
+pub mod fractional_voc;
+pub mod global_scan;
 pub mod mppt;
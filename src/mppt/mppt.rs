@@ -1,4 +1,97 @@
+use crate::signal::lut::Interp1d;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MpptError {
+    InvertedLimits,
+    NonPositiveStep,
+    UnconfiguredLimits,
+}
+
+pub(super) fn validate_config(
+    v_out_min: f32,
+    v_out_max: f32,
+    step_size: f32,
+) -> Result<(), MpptError> {
+    if v_out_min == 0.0 && v_out_max == 0.0 {
+        Err(MpptError::UnconfiguredLimits)
+    } else if v_out_min > v_out_max {
+        Err(MpptError::InvertedLimits)
+    } else if step_size <= 0.0 {
+        Err(MpptError::NonPositiveStep)
+    } else {
+        Ok(())
+    }
+}
+
+/* Clamp that tolerates inverted limits, which are reported through MpptError instead */
+fn clamp_reference(v: f32, v_out_min: f32, v_out_max: f32) -> f32 {
+    if v > v_out_max {
+        v_out_max
+    } else if v < v_out_min {
+        v_out_min
+    } else {
+        v
+    }
+}
+
+/* Moves v towards target by at most step */
+fn approach(v: f32, target: f32, step: f32) -> f32 {
+    if v + step < target {
+        v + step
+    } else if v - step > target {
+        v - step
+    } else {
+        target
+    }
+}
+
+const SEED_POINTS: usize = 8;
+
+/* Expected maximum power point voltage against irradiance at a reference cell temperature,
+with a linear temperature correction, for starting the trackers near the MPP */
+#[derive(Clone, Copy)]
+pub struct SeedTable {
+    irradiance: [f32; SEED_POINTS], /* Breakpoints in W/m^2, ascending */
+    vmp: [f32; SEED_POINTS],        /* MPP voltage at each breakpoint */
+    len: usize,                     /* Breakpoints in use */
+    temp_coeff: f32,                /* MPP voltage temperature coefficient in V/degC */
+    reference_temp: f32,            /* Cell temperature the table was taken at */
+}
+
+impl SeedTable {
+    /* Copies up to 8 points, beyond that the table is truncated */
+    pub fn new(irradiance: &[f32], vmp: &[f32], temp_coeff: f32, reference_temp: f32) -> SeedTable {
+        let mut table = SeedTable {
+            irradiance: [0.0; SEED_POINTS],
+            vmp: [0.0; SEED_POINTS],
+            len: 0,
+            temp_coeff,
+            reference_temp,
+        };
+        for ((g, v), (&g_in, &v_in)) in table
+            .irradiance
+            .iter_mut()
+            .zip(table.vmp.iter_mut())
+            .zip(irradiance.iter().zip(vmp.iter()))
+        {
+            *g = g_in;
+            *v = v_in;
+            table.len += 1;
+        }
+        table
+    }
+    /* Held at the end values outside the irradiance range */
+    pub fn vmp_at(&self, irradiance: f32, temperature: f32) -> f32 {
+        let table = Interp1d::new(&self.irradiance[..self.len], &self.vmp[..self.len]);
+        table.eval_clamped(irradiance) + self.temp_coeff * (temperature - self.reference_temp)
+    }
+}
+
 pub mod perturb_and_observe {
+    use super::{MpptError, SeedTable};
+
+    const DITHER_SEED: u32 = 0x2545_f491;
+
     enum VMPPAction {
         INCREMENT,
         DECREMENT,
@@ -18,6 +111,22 @@ pub mod perturb_and_observe {
         mppt_v_out: f32,
         mppt_enable: bool,
         mppt_first: bool,
+        config_error: Option<MpptError>,
+        temp_coeff: f32,         /* PV voltage temperature coefficient in V/degC */
+        reference_temp: f32,     /* Cell temperature at which the limits were specified */
+        v_out_shift: f32,        /* Temperature shift applied to the voltage limits */
+        cell_temp: Option<f32>,  /* Last temperature given to calculate_with_temp */
+        averaging: u32,          /* Measurements averaged per perturbation decision */
+        avg_count: u32,          /* Measurements accumulated towards the next decision */
+        avg_sum_i: f32,          /* Sum of accumulated currents */
+        avg_sum_v: f32,          /* Sum of accumulated voltages */
+        averaged_power: f32,     /* Power of the last averaged measurement */
+        idle_v_out: Option<f32>, /* Reference approached while disabled, None holds it */
+        resume_step: bool,       /* Perturb once on the first decision after re-enabling */
+        dither_amplitude: f32,   /* Peak random offset added to the reference */
+        dither_state: u32,       /* Xorshift generator state */
+        dither_offset: f32,      /* Offset currently applied to the reference */
+        seed_table: Option<SeedTable>, /* Expected MPP voltage for seed_from_table */
     }
     impl MPPT {
         pub fn new() -> MPPT {
@@ -36,12 +145,241 @@ pub mod perturb_and_observe {
                 mppt_v_out: 0.0,
                 mppt_enable: true,
                 mppt_first: true,
+                config_error: Some(MpptError::UnconfiguredLimits),
+                temp_coeff: 0.0,
+                reference_temp: 25.0,
+                v_out_shift: 0.0,
+                cell_temp: None,
+                averaging: 1,
+                avg_count: 0,
+                avg_sum_i: 0.0,
+                avg_sum_v: 0.0,
+                averaged_power: 0.0,
+                idle_v_out: None,
+                resume_step: false,
+                dither_amplitude: 0.0,
+                dither_state: DITHER_SEED,
+                dither_offset: 0.0,
+                seed_table: None,
             }
         }
         pub fn get_mppt_v_out(&self) -> f32 {
             self.mppt_v_out
         }
+        pub fn set_v_out_limits(&mut self, v_out_min: f32, v_out_max: f32) {
+            self.mppt_v_out_min = v_out_min;
+            self.mppt_v_out_max = v_out_max;
+            self.config_error = self.validate().err();
+        }
+        pub fn set_step_size(&mut self, step_size: f32) {
+            self.step_size = step_size;
+            self.config_error = self.validate().err();
+        }
+        pub fn validate(&self) -> Result<(), MpptError> {
+            super::validate_config(self.mppt_v_out_min, self.mppt_v_out_max, self.step_size)
+        }
+        pub fn get_error(&self) -> Option<MpptError> {
+            self.config_error
+        }
+        /* The shift is recomputed from the last cell temperature, so a zero coefficient
+        restores the configured limits at once. calculate() keeps using the shift from the
+        last calculate_with_temp call. */
+        pub fn set_temperature_compensation(
+            &mut self,
+            temp_coeff_v_per_c: f32,
+            reference_temp: f32,
+        ) {
+            self.temp_coeff = temp_coeff_v_per_c;
+            self.reference_temp = reference_temp;
+            self.v_out_shift = match self.cell_temp {
+                Some(cell_temp) if temp_coeff_v_per_c != 0.0 => {
+                    temp_coeff_v_per_c * (cell_temp - reference_temp)
+                }
+                _ => 0.0,
+            };
+        }
+        pub fn get_effective_limits(&self) -> (f32, f32) {
+            (
+                self.mppt_v_out_min + self.v_out_shift,
+                self.mppt_v_out_max + self.v_out_shift,
+            )
+        }
+        pub fn calculate_with_temp(&mut self, pv_i: f32, pv_v: f32, cell_temp: f32) {
+            self.cell_temp = Some(cell_temp);
+            self.v_out_shift = self.temp_coeff * (cell_temp - self.reference_temp);
+            self.calculate(pv_i, pv_v);
+        }
+        /* Restarts the algorithm as on a first call, keeping limits, step and the reference */
+        pub fn reset(&mut self) {
+            self.pv_i = 0.0;
+            self.pv_v = 0.0;
+            self.pv_v_prev = 0.0;
+            self.pv_power = 0.0;
+            self.pv_power_prev = 0.0;
+            self.delta_pv_power = 0.0;
+            self.mppt_v_out_action = VMPPAction::INCREMENT;
+            self.mppt_first = true;
+            self.avg_count = 0;
+            self.avg_sum_i = 0.0;
+            self.avg_sum_v = 0.0;
+            self.averaged_power = 0.0;
+            // Keep the undithered reference
+            self.mppt_v_out -= self.dither_offset;
+            self.dither_offset = 0.0;
+        }
+        pub fn reset_to(&mut self, voltage: f32) {
+            self.reset();
+            let (v_out_min, v_out_max) = self.get_effective_limits();
+            self.mppt_v_out = super::clamp_reference(voltage, v_out_min, v_out_max);
+        }
+        /* Starting reference, only accepted before the first decision or after reset() so it
+        never overrides a running tracker. Returns whether it was applied. */
+        pub fn set_initial_voltage(&mut self, v: f32) -> bool {
+            if !self.mppt_first || self.config_error.is_some() {
+                return false;
+            }
+            let (v_out_min, v_out_max) = self.get_effective_limits();
+            self.mppt_v_out = super::clamp_reference(v, v_out_min, v_out_max);
+            self.dither_offset = 0.0;
+            true
+        }
+        pub fn set_seed_table(&mut self, table: SeedTable) {
+            self.seed_table = Some(table);
+        }
+        /* set_initial_voltage with the table's expected MPP voltage, false without a table */
+        pub fn seed_from_table(&mut self, irradiance: f32, temperature: f32) -> bool {
+            match self.seed_table {
+                Some(table) => self.set_initial_voltage(table.vmp_at(irradiance, temperature)),
+                None => false,
+            }
+        }
+        /* Average this many measurements per decision, the reference is held in between so the
+        perturbation period becomes samples times the call period */
+        pub fn set_averaging(&mut self, samples: u32) {
+            self.averaging = if samples < 1 { 1 } else { samples };
+            self.avg_count = 0;
+            self.avg_sum_i = 0.0;
+            self.avg_sum_v = 0.0;
+        }
+        pub fn get_averaged_power(&self) -> f32 {
+            self.averaged_power
+        }
+        /* Accumulates one measurement, returns the averaged pair once the window is complete */
+        fn average(&mut self, pv_i: f32, pv_v: f32) -> Option<(f32, f32)> {
+            self.avg_sum_i += pv_i;
+            self.avg_sum_v += pv_v;
+            self.avg_count += 1;
+            if self.avg_count < self.averaging {
+                return None;
+            }
+            let n = self.avg_count as f32;
+            let averaged = (self.avg_sum_i / n, self.avg_sum_v / n);
+            self.avg_count = 0;
+            self.avg_sum_i = 0.0;
+            self.avg_sum_v = 0.0;
+            self.averaged_power = averaged.0 * averaged.1;
+            Some(averaged)
+        }
+        /* While disabled measurements are still recorded so the first enabled decision has a
+        valid history, and the reference is held or slewed by step_size towards the idle value */
+        pub fn set_enabled(&mut self, enable: bool) {
+            if enable && !self.mppt_enable {
+                self.resume_step = true;
+            }
+            self.mppt_enable = enable;
+        }
+        pub fn get_enabled(&self) -> bool {
+            self.mppt_enable
+        }
+        pub fn set_idle_reference(&mut self, idle_v_out: Option<f32>) {
+            self.idle_v_out = idle_v_out;
+        }
+        /* Adds a pseudo-random offset of up to amplitude to the reference on every decision,
+        limited to a quarter of the step size so it cannot mask the perturbation itself */
+        pub fn set_dither(&mut self, amplitude: f32) {
+            self.dither_amplitude = amplitude.abs();
+        }
+        /* The sequence repeats for a given seed, zero selects the default seed */
+        pub fn set_dither_seed(&mut self, seed: u32) {
+            self.dither_state = if seed == 0 { DITHER_SEED } else { seed };
+        }
+        fn dither(&mut self) {
+            // Xorshift32, the top 24 bits give a uniform value in [-1, 1)
+            let mut x = self.dither_state;
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            self.dither_state = x;
+            let unit = (x >> 8) as f32 / 8_388_608.0 - 1.0;
+            let amplitude = self.dither_amplitude.min(0.25 * self.step_size);
+            // Replace rather than accumulate the offset so the reference does not random walk
+            let offset = unit * amplitude;
+            let (v_out_min, v_out_max) = self.get_effective_limits();
+            self.mppt_v_out = super::clamp_reference(
+                self.mppt_v_out - self.dither_offset + offset,
+                v_out_min,
+                v_out_max,
+            );
+            self.dither_offset = offset;
+        }
+        fn record(&mut self, pv_i: f32, pv_v: f32) {
+            self.pv_i = pv_i;
+            self.pv_v = pv_v;
+            self.pv_power = pv_i * pv_v;
+            self.pv_v_prev = self.pv_v;
+            self.pv_power_prev = self.pv_power;
+            self.mppt_first = false;
+        }
+        fn record_disabled(&mut self, pv_i: f32, pv_v: f32) {
+            self.record(pv_i, pv_v);
+            if let Some(idle) = self.idle_v_out {
+                let (v_out_min, v_out_max) = self.get_effective_limits();
+                let target = super::clamp_reference(idle, v_out_min, v_out_max);
+                self.mppt_v_out = super::approach(self.mppt_v_out, target, self.step_size);
+                self.dither_offset = 0.0;
+            }
+        }
+        fn apply_action(&mut self) {
+            let (v_out_min, v_out_max) = self.get_effective_limits();
+            match self.mppt_v_out_action {
+                VMPPAction::INCREMENT => {
+                    if self.mppt_v_out + self.step_size > v_out_max {
+                        self.mppt_v_out = v_out_max;
+                        self.dither_offset = 0.0;
+                    } else {
+                        self.mppt_v_out += self.step_size;
+                    }
+                }
+                VMPPAction::DECREMENT => {
+                    if self.mppt_v_out - self.step_size < v_out_min {
+                        self.mppt_v_out = v_out_min;
+                        self.dither_offset = 0.0;
+                    } else {
+                        self.mppt_v_out -= self.step_size;
+                    }
+                }
+            }
+        }
         pub fn calculate(&mut self, pv_i: f32, pv_v: f32) {
+            if self.config_error.is_some() {
+                return;
+            }
+            let (pv_i, pv_v) = match self.average(pv_i, pv_v) {
+                Some(averaged) => averaged,
+                None => return,
+            };
+            if !self.mppt_enable {
+                self.record_disabled(pv_i, pv_v);
+                return;
+            }
+            if self.resume_step {
+                // The history recorded while disabled shows no change, restart the climb with
+                // one step in the last direction instead of stalling at the held reference
+                self.resume_step = false;
+                self.record(pv_i, pv_v);
+                self.apply_action();
+                return;
+            }
             if self.mppt_first {
                 self.pv_v_prev = self.pv_v;
                 self.pv_power_prev = self.pv_power;
@@ -69,31 +407,21 @@ pub mod perturb_and_observe {
                             self.mppt_v_out_action = VMPPAction::INCREMENT;
                         }
                     }
-                    match self.mppt_v_out_action {
-                        VMPPAction::INCREMENT => {
-                            if self.mppt_v_out + self.step_size > self.mppt_v_out_max {
-                                self.mppt_v_out = self.mppt_v_out_max;
-                            } else {
-                                self.mppt_v_out += self.step_size;
-                            }
-                        }
-                        VMPPAction::DECREMENT => {
-                            if self.mppt_v_out - self.step_size < self.mppt_v_out_min {
-                                self.mppt_v_out = self.mppt_v_out_min;
-                            } else {
-                                self.mppt_v_out -= self.step_size;
-                            }
-                        }
-                    }
+                    self.apply_action();
                 }
                 self.pv_v_prev = self.pv_v;
                 self.pv_power_prev = self.pv_power;
+                if self.dither_amplitude > 0.0 {
+                    self.dither();
+                }
             }
         }
     }
 }
 
 pub mod incremental_conductance {
+    use super::{MpptError, SeedTable};
+
     enum VMPPAction {
         INCREMENT,
         DECREMENT,
@@ -118,6 +446,15 @@ pub mod incremental_conductance {
         pv_i_old: f32,
         mppt_enable: bool,
         mppt_first: bool,
+        config_error: Option<MpptError>,
+        averaging: u32,          /* Measurements averaged per perturbation decision */
+        avg_count: u32,          /* Measurements accumulated towards the next decision */
+        avg_sum_i: f32,          /* Sum of accumulated currents */
+        avg_sum_v: f32,          /* Sum of accumulated voltages */
+        averaged_power: f32,     /* Power of the last averaged measurement */
+        idle_v_out: Option<f32>, /* Reference approached while disabled, None holds it */
+        resume_step: bool,       /* Perturb once on the first decision after re-enabling */
+        seed_table: Option<SeedTable>, /* Expected MPP voltage for seed_from_table */
     }
 
     impl MPPT {
@@ -142,12 +479,169 @@ pub mod incremental_conductance {
                 pv_i_old: 0.0,
                 mppt_enable: true,
                 mppt_first: true,
+                config_error: Some(MpptError::UnconfiguredLimits),
+                averaging: 1,
+                avg_count: 0,
+                avg_sum_i: 0.0,
+                avg_sum_v: 0.0,
+                averaged_power: 0.0,
+                idle_v_out: None,
+                resume_step: false,
+                seed_table: None,
             }
         }
         pub fn get_mppt_v_out(&self) -> f32 {
             self.mppt_v_out
         }
+        pub fn set_v_out_limits(&mut self, v_out_min: f32, v_out_max: f32) {
+            self.mppt_v_out_min = v_out_min;
+            self.mppt_v_out_max = v_out_max;
+            self.config_error = self.validate().err();
+        }
+        pub fn set_step_size(&mut self, step_size: f32) {
+            self.step_size = step_size;
+            self.config_error = self.validate().err();
+        }
+        pub fn validate(&self) -> Result<(), MpptError> {
+            super::validate_config(self.mppt_v_out_min, self.mppt_v_out_max, self.step_size)
+        }
+        pub fn get_error(&self) -> Option<MpptError> {
+            self.config_error
+        }
+        /* Restarts the algorithm as on a first call, keeping limits, step and the reference */
+        pub fn reset(&mut self) {
+            self.pv_i = 0.0;
+            self.pv_v = 0.0;
+            self.conductance = 0.0;
+            self.incremental_conductance = 0.0;
+            self.delta_pv_v = 0.0;
+            self.delta_pv_i = 0.0;
+            self.pv_v_old = 0.0;
+            self.pv_i_old = 0.0;
+            self.mppt_v_out_action = VMPPAction::INCREMENT;
+            self.mppt_first = true;
+            self.avg_count = 0;
+            self.avg_sum_i = 0.0;
+            self.avg_sum_v = 0.0;
+            self.averaged_power = 0.0;
+        }
+        pub fn reset_to(&mut self, voltage: f32) {
+            self.reset();
+            self.mppt_v_out =
+                super::clamp_reference(voltage, self.mppt_v_out_min, self.mppt_v_out_max);
+        }
+        /* Starting reference, only accepted before the first decision or after reset() so it
+        never overrides a running tracker. Returns whether it was applied. */
+        pub fn set_initial_voltage(&mut self, v: f32) -> bool {
+            if !self.mppt_first || self.config_error.is_some() {
+                return false;
+            }
+            self.mppt_v_out = super::clamp_reference(v, self.mppt_v_out_min, self.mppt_v_out_max);
+            true
+        }
+        pub fn set_seed_table(&mut self, table: SeedTable) {
+            self.seed_table = Some(table);
+        }
+        /* set_initial_voltage with the table's expected MPP voltage, false without a table */
+        pub fn seed_from_table(&mut self, irradiance: f32, temperature: f32) -> bool {
+            match self.seed_table {
+                Some(table) => self.set_initial_voltage(table.vmp_at(irradiance, temperature)),
+                None => false,
+            }
+        }
+        /* Average this many measurements per decision, the reference is held in between so the
+        perturbation period becomes samples times the call period */
+        pub fn set_averaging(&mut self, samples: u32) {
+            self.averaging = if samples < 1 { 1 } else { samples };
+            self.avg_count = 0;
+            self.avg_sum_i = 0.0;
+            self.avg_sum_v = 0.0;
+        }
+        pub fn get_averaged_power(&self) -> f32 {
+            self.averaged_power
+        }
+        /* Accumulates one measurement, returns the averaged pair once the window is complete */
+        fn average(&mut self, pv_i: f32, pv_v: f32) -> Option<(f32, f32)> {
+            self.avg_sum_i += pv_i;
+            self.avg_sum_v += pv_v;
+            self.avg_count += 1;
+            if self.avg_count < self.averaging {
+                return None;
+            }
+            let n = self.avg_count as f32;
+            let averaged = (self.avg_sum_i / n, self.avg_sum_v / n);
+            self.avg_count = 0;
+            self.avg_sum_i = 0.0;
+            self.avg_sum_v = 0.0;
+            self.averaged_power = averaged.0 * averaged.1;
+            Some(averaged)
+        }
+        /* While disabled measurements are still recorded so the first enabled decision has a
+        valid history, and the reference is held or slewed by step_size towards the idle value */
+        pub fn set_enabled(&mut self, enable: bool) {
+            if enable && !self.mppt_enable {
+                self.resume_step = true;
+            }
+            self.mppt_enable = enable;
+        }
+        pub fn get_enabled(&self) -> bool {
+            self.mppt_enable
+        }
+        pub fn set_idle_reference(&mut self, idle_v_out: Option<f32>) {
+            self.idle_v_out = idle_v_out;
+        }
+        fn record(&mut self, pv_i: f32, pv_v: f32) {
+            self.pv_i = pv_i;
+            self.pv_v = pv_v;
+            self.pv_v_old = pv_v;
+            self.pv_i_old = pv_i;
+            self.mppt_first = false;
+        }
+        fn record_disabled(&mut self, pv_i: f32, pv_v: f32) {
+            self.record(pv_i, pv_v);
+            if let Some(idle) = self.idle_v_out {
+                let target = super::clamp_reference(idle, self.mppt_v_out_min, self.mppt_v_out_max);
+                self.mppt_v_out = super::approach(self.mppt_v_out, target, self.step_size);
+            }
+        }
+        fn apply_action(&mut self) {
+            match self.mppt_v_out_action {
+                VMPPAction::INCREMENT => {
+                    if self.mppt_v_out + self.step_size > self.mppt_v_out_max {
+                        self.mppt_v_out = self.mppt_v_out_max;
+                    } else {
+                        self.mppt_v_out += self.step_size;
+                    }
+                }
+                VMPPAction::DECREMENT => {
+                    if self.mppt_v_out - self.step_size < self.mppt_v_out_min {
+                        self.mppt_v_out = self.mppt_v_out_min;
+                    } else {
+                        self.mppt_v_out -= self.step_size;
+                    }
+                }
+            }
+        }
         pub fn calculate(&mut self, pv_i: f32, pv_v: f32) {
+            if self.config_error.is_some() {
+                return;
+            }
+            let (pv_i, pv_v) = match self.average(pv_i, pv_v) {
+                Some(averaged) => averaged,
+                None => return,
+            };
+            if !self.mppt_enable {
+                self.record_disabled(pv_i, pv_v);
+                return;
+            }
+            if self.resume_step {
+                // The history recorded while disabled shows no change, restart the climb with
+                // one step in the last direction instead of stalling at the held reference
+                self.resume_step = false;
+                self.record(pv_i, pv_v);
+                self.apply_action();
+                return;
+            }
             if self.mppt_first {
                 self.pv_v_old = self.pv_v;
                 self.pv_i_old = self.pv_i;
@@ -206,22 +700,7 @@ pub mod incremental_conductance {
                             }
                         }
                     }
-                    match self.mppt_v_out_action {
-                        VMPPAction::INCREMENT => {
-                            if self.mppt_v_out + self.step_size > self.mppt_v_out_max {
-                                self.mppt_v_out = self.mppt_v_out_max;
-                            } else {
-                                self.mppt_v_out += self.step_size;
-                            }
-                        }
-                        VMPPAction::DECREMENT => {
-                            if self.mppt_v_out - self.step_size < self.mppt_v_out_min {
-                                self.mppt_v_out = self.mppt_v_out_min;
-                            } else {
-                                self.mppt_v_out -= self.step_size;
-                            }
-                        }
-                    }
+                    self.apply_action();
                 }
             }
             self.pv_v_old = self.pv_v;
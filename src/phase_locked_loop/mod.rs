@@ -1 +1,2 @@
+pub mod msogi;
 pub mod sogi;
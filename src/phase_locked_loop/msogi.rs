@@ -0,0 +1,107 @@
+use crate::math::float::{atan2, sqrt};
+use core::f32::consts::{SQRT_2, TAU};
+
+/* SOGI damping gain, sqrt(2) trades settling time against selectivity */
+const K: f32 = SQRT_2;
+
+#[derive(Clone, Copy)]
+struct SogiStage {
+    order: u8,
+    b0: f32,
+    a1: f32,
+    a2: f32,
+    qb0: f32,
+    gain: f32,    /* 1 / (1 - b0), solves the direct feed-through of the cross-feedback */
+    u: [f32; 3],  /* Stage input history */
+    v: [f32; 3],  /* In-phase estimate */
+    qv: [f32; 3], /* Quadrature estimate */
+}
+
+impl SogiStage {
+    fn new(order: u8, fundamental_freq: f32, delta_t: f32) -> SogiStage {
+        let wn = TAU * fundamental_freq * order as f32;
+        let x = 2.0 * K * wn * delta_t;
+        let y = wn * delta_t * wn * delta_t;
+        let temp = 1.0 / (x + y + 4.0);
+        SogiStage {
+            order,
+            b0: x * temp,
+            a1: 2.0 * (4.0 - y) * temp,
+            a2: (x - y - 4.0) * temp,
+            qb0: K * y * temp,
+            gain: 1.0 / (1.0 - x * temp),
+            u: [0.0; 3],
+            v: [0.0; 3],
+            qv: [0.0; 3],
+        }
+    }
+    /* In-phase output excluding the direct feed-through term b0 * input */
+    fn free_response(&self) -> f32 {
+        -self.b0 * self.u[2] + self.a1 * self.v[1] + self.a2 * self.v[2]
+    }
+    fn update(&mut self, input: f32) {
+        self.u[0] = input;
+        self.v[0] = self.b0 * (self.u[0] - self.u[2]) + self.a1 * self.v[1] + self.a2 * self.v[2];
+        self.qv[0] = self.qb0 * (self.u[0] + 2.0 * self.u[1] + self.u[2])
+            + self.a1 * self.qv[1]
+            + self.a2 * self.qv[2];
+        self.u[2] = self.u[1];
+        self.u[1] = self.u[0];
+        self.v[2] = self.v[1];
+        self.v[1] = self.v[0];
+        self.qv[2] = self.qv[1];
+        self.qv[1] = self.qv[0];
+    }
+    fn amplitude_phase(&self) -> (f32, f32) {
+        (
+            sqrt(self.v[0] * self.v[0] + self.qv[0] * self.qv[0]),
+            atan2(self.v[0], -self.qv[0]),
+        )
+    }
+}
+
+pub struct MultiSogi<const H: usize> {
+    fundamental: SogiStage,
+    harmonics: [SogiStage; H],
+}
+
+impl<const H: usize> MultiSogi<H> {
+    pub fn new(fundamental_freq: f32, harmonics: [u8; H], sample_rate: f32) -> MultiSogi<H> {
+        let delta_t = 1.0 / sample_rate;
+        MultiSogi {
+            fundamental: SogiStage::new(1, fundamental_freq, delta_t),
+            harmonics: core::array::from_fn(|n| {
+                SogiStage::new(harmonics[n], fundamental_freq, delta_t)
+            }),
+        }
+    }
+    pub fn update(&mut self, input: f32) -> f32 {
+        // Each stage sees the input minus the other stages' estimates (cross-feedback). With
+        // v_n = g_n * (input - sum + v_n) + r_n the sum of all estimates is solved for directly,
+        // so the cross-feedback has no extra sample of delay.
+        let mut g_sum = 0.0;
+        let mut h_sum = 0.0;
+        for stage in core::iter::once(&self.fundamental).chain(self.harmonics.iter()) {
+            g_sum += stage.b0 * stage.gain;
+            h_sum += stage.free_response() * stage.gain;
+        }
+        let total = (g_sum * input + h_sum) / (1.0 + g_sum);
+        for stage in core::iter::once(&mut self.fundamental).chain(self.harmonics.iter_mut()) {
+            let v = (stage.b0 * (input - total) + stage.free_response()) * stage.gain;
+            stage.update(input - total + v);
+        }
+        self.fundamental.v[0]
+    }
+    pub fn get_fundamental(&self) -> (f32, f32) {
+        (self.fundamental.v[0], self.fundamental.qv[0])
+    }
+    pub fn get_harmonic(&self, order: u8) -> (f32, f32) {
+        if order == 1 {
+            return self.fundamental.amplitude_phase();
+        }
+        match self.harmonics.iter().find(|stage| stage.order == order) {
+            Some(stage) => stage.amplitude_phase(),
+            None => (0.0, 0.0),
+        }
+    }
+}
@@ -1,3 +1,18 @@
+use crate::math::float::{cos, sin, sqrt, wrap_2pi};
+use crate::signal::filter::biquad::Biquad;
+use core::f32::consts::{PI, TAU};
+
+/* Loop filter PI gains, B0 = 166.877556 and B1 = -166.322444 at 20 kHz */
+const LF_KP: f32 = 166.6;
+const LF_KI: f32 = 11102.24;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreFilter {
+    None,
+    DcBlock { cutoff: f32 },         /* First-order high-pass, cutoff in Hz */
+    Notch { harmonic: u32, q: f32 }, /* Notch at a multiple of the nominal frequency */
+}
+
 pub struct OrthogonalSignalGenerator {
     k: f32,
     x: f32,
@@ -30,6 +45,23 @@ pub struct SOGI {
     cos: f32,                             /* Cosine of grid phase angle */
     sin: f32,                             /* Sine of grid phase angle */
     delta_t: f32,                         /* 1/Frequency of calling PLL routine */
+    wn: f32,                              /* SOGI center frequency in rad/s */
+    fll_enable: bool,                     /* Track the SOGI center frequency to fo */
+    fo_min: f32,                          /* Lower frequency estimate limit */
+    fo_max: f32,                          /* Upper frequency estimate limit */
+    healthy: bool,                        /* Last call ran on a finite input and state */
+    prefilter: PreFilter,                 /* Input conditioning ahead of the SOGI */
+    prefilter_stage: Biquad,              /* Realization of the selected pre-filter */
+    lock_threshold: f32,                  /* Normalized phase error accepted as locked */
+    lock_time: f32,                       /* Time the error must stay below the threshold */
+    lock_timer: f32,                      /* Time spent below the threshold so far */
+    lock_error: f32,                      /* Filtered normalized phase error */
+    locked: bool,                         /* Lock detector state */
+    warmup_freerun: bool,                 /* Publish the free-running phase until locked */
+    freerun_theta: f32,                   /* Phase advancing at the nominal frequency */
+    jump_level: f32,                      /* Residual rise corresponding to the jump threshold */
+    residual_baseline: f32,               /* Slowly filtered normalized SOGI residual */
+    jump_hold: f32,                       /* Time the phase jump flag stays raised */
     lpf_coeff: NotchFilter,               /* Notch filter coefficients */
     osg_coeff: OrthogonalSignalGenerator, /* Orthogonal signal generator coefficients */
 }
@@ -37,7 +69,6 @@ pub struct SOGI {
 impl SOGI {
     pub fn new(fnom: f32, delta_t: f32) -> SOGI {
         let mut sogi = SOGI {
-            // TODO
             u: [0.0; 3],
             osg_u: [0.0; 3],
             osg_qu: [0.0; 3],
@@ -45,11 +76,28 @@ impl SOGI {
             u_d: [0.0; 2],
             ylf: [0.0; 2],
             fo: 0.0,
-            fnom: fnom,
+            fnom,
             theta: [0.0; 2],
             cos: 0.0,
             sin: 0.0,
-            delta_t: delta_t,
+            delta_t,
+            wn: 0.0,
+            fll_enable: false,
+            fo_min: f32::NEG_INFINITY,
+            fo_max: f32::INFINITY,
+            healthy: true,
+            prefilter: PreFilter::None,
+            prefilter_stage: Biquad::identity(),
+            lock_threshold: 0.05,
+            lock_time: 0.1,
+            lock_timer: 0.0,
+            lock_error: 1.0,
+            locked: false,
+            warmup_freerun: false,
+            freerun_theta: 0.0,
+            jump_level: f32::INFINITY,
+            residual_baseline: 0.0,
+            jump_hold: 0.0,
             lpf_coeff: NotchFilter {
                 a1: 0.0,
                 b0: 0.0,
@@ -73,11 +121,311 @@ impl SOGI {
     }
     pub fn init(&mut self, fnom: f32) {
         self.fnom = fnom;
+        self.fo = fnom;
+        self.wn = TAU * fnom;
+        self.lf_coeff_update();
+        self.coeff_update();
+    }
+    /* Clears the filter histories and restarts from the nominal frequency */
+    pub fn reset(&mut self) {
+        self.u = [0.0; 3];
+        self.osg_u = [0.0; 3];
+        self.osg_qu = [0.0; 3];
+        self.u_q = [0.0; 2];
+        self.u_d = [0.0; 2];
+        self.ylf = [0.0; 2];
+        self.theta = [0.0; 2];
+        self.cos = 0.0;
+        self.sin = 0.0;
+        self.prefilter_stage.reset();
+        self.lock_timer = 0.0;
+        self.lock_error = 1.0;
+        self.locked = false;
+        self.freerun_theta = 0.0;
+        self.residual_baseline = 0.0;
+        self.jump_hold = 0.0;
+        self.init(self.fnom);
+    }
+    /* Rescales every dt-dependent coefficient and re-times the SOGI history to the new rate */
+    pub fn set_sample_time(&mut self, delta_t: f32) {
+        self.delta_t = delta_t;
+        self.lf_coeff_update();
+        self.coeff_update();
+        self.prefilter_update();
+        // Rotate the latest in-phase/quadrature pair back by one new sample period so the
+        // difference equations see a history consistent with the new rate
+        let phi = self.wn * delta_t;
+        let (v, qv) = (self.osg_u[1], self.osg_qu[1]);
+        self.osg_u[2] = v * cos(phi) + qv * sin(phi);
+        self.osg_qu[2] = qv * cos(phi) - v * sin(phi);
+        self.u[2] = self.osg_u[2];
+    }
+    pub fn get_sample_time(&self) -> f32 {
+        self.delta_t
+    }
+    /* The PLL locks to the filtered input, so theta leads the raw input by
+    get_prefilter_phase() at the nominal frequency */
+    pub fn set_prefilter(&mut self, prefilter: PreFilter) {
+        self.prefilter = prefilter;
+        self.prefilter_update();
+    }
+    fn prefilter_update(&mut self) {
+        let sample_rate = 1.0 / self.delta_t;
+        self.prefilter_stage = match self.prefilter {
+            PreFilter::None => Biquad::identity(),
+            PreFilter::DcBlock { cutoff } => Biquad::dc_block(cutoff, sample_rate),
+            PreFilter::Notch { harmonic, q } => {
+                Biquad::notch(harmonic as f32 * self.fnom, q, sample_rate)
+            }
+        };
+    }
+    /* Phase shift of the pre-filter at the nominal frequency, positive for a lead */
+    pub fn get_prefilter_phase(&self) -> f32 {
+        match self.prefilter {
+            PreFilter::None => 0.0,
+            _ => self.prefilter_stage.phase_at(self.fnom, 1.0 / self.delta_t),
+        }
+    }
+    fn lf_coeff_update(&mut self) {
+        // Tustin discretization of the PI loop filter, the integrator state is in Hz so it
+        // remains valid across sample time changes
+        self.lpf_coeff.b0 = LF_KP + 0.5 * LF_KI * self.delta_t;
+        self.lpf_coeff.b1 = -LF_KP + 0.5 * LF_KI * self.delta_t;
+        self.lpf_coeff.a1 = -1.0;
+    }
+    pub fn set_frequency_adaptive(&mut self, enable: bool) {
+        self.fll_enable = enable;
+        if !enable {
+            self.wn = TAU * self.fnom;
+            self.coeff_update();
+        }
+    }
+    /* Clamps the frequency estimate, the loop filter state is held at the limit so it cannot
+    wind up while the clamp is active */
+    pub fn set_frequency_limits(&mut self, min_hz: f32, max_hz: f32) {
+        self.fo_min = min_hz;
+        self.fo_max = max_hz;
+    }
+    /* Lock is declared once the filtered phase error stays below threshold for time
+    seconds and dropped when it exceeds twice the threshold */
+    pub fn set_lock_detection(&mut self, threshold: f32, time: f32) {
+        self.lock_threshold = threshold;
+        self.lock_time = time;
+    }
+    /* While unlocked get_phase returns a phase free-running at the nominal frequency so a
+    feedforward angle is available before lock */
+    pub fn set_warmup_freerun(&mut self, enable: bool) {
+        self.warmup_freerun = enable;
+    }
+    /* A phase step of delta raises the normalized SOGI residual by up to 2 sin(delta / 2)
+    within half a cycle, a rise beyond that for the threshold over the slowly filtered residual
+    flags a jump. Slow disturbances such as frequency drift or harmonics move the baseline
+    instead. The flag stays raised for one nominal cycle. Disabled by default. */
+    pub fn set_phase_jump_threshold(&mut self, radians: f32) {
+        self.jump_level = 2.0 * sin(0.5 * radians.abs().min(PI));
+    }
+    /* Starts from a known phase, e.g. from a zero-crossing measurement. The SOGI history is
+    loaded with a unit amplitude signal at that phase, the per-unit input the loop expects,
+    so the first samples see no step. A wrong seed is pulled in like any other phase error. */
+    pub fn seed_phase(&mut self, estimated_phase: f32) {
+        let theta = wrap_2pi(estimated_phase);
+        let previous = theta - self.wn * self.delta_t;
+        self.theta = [theta; 2];
+        self.sin = sin(theta);
+        self.cos = cos(theta);
+        // In lock the in-phase output follows sin(theta) and the quadrature -cos(theta)
+        self.osg_u = [self.sin, self.sin, sin(previous)];
+        self.osg_qu = [-self.cos, -self.cos, -cos(previous)];
+        self.u = self.osg_u;
+        self.u_q = [0.0; 2];
+        self.u_d = [-1.0; 2];
+    }
+    /* Loads the loop filter so the estimate starts at estimated_freq, within the frequency
+    limits, and moves the SOGI center there when frequency adaptation is on */
+    pub fn seed_frequency(&mut self, estimated_freq: f32) {
+        let mut fo = estimated_freq;
+        if fo > self.fo_max {
+            fo = self.fo_max;
+        } else if fo < self.fo_min {
+            fo = self.fo_min;
+        }
+        self.ylf = [fo - self.fnom; 2];
+        self.fo = fo;
+        if self.fll_enable {
+            self.wn = TAU * fo;
+            self.coeff_update();
+        }
     }
     pub fn coeff_update(&mut self) {
-        // TODO
+        let osgx = 2.0 * 0.5 * self.wn * self.delta_t;
+        let osgy = self.wn * self.delta_t * self.wn * self.delta_t;
+        let temp = 1.0 / (osgx + osgy + 4.0);
+        self.osg_coeff.k = 0.5;
+        self.osg_coeff.x = osgx;
+        self.osg_coeff.y = osgy;
+        self.osg_coeff.b0 = osgx * temp;
+        self.osg_coeff.b2 = -self.osg_coeff.b0;
+        self.osg_coeff.a1 = 2.0 * (4.0 - osgy) * temp;
+        self.osg_coeff.a2 = (osgx - osgy - 4.0) * temp;
+        self.osg_coeff.qb0 = 0.5 * osgy * temp;
+        self.osg_coeff.qb1 = 2.0 * self.osg_coeff.qb0;
+        self.osg_coeff.qb2 = self.osg_coeff.qb0;
+    }
+    pub fn run(&mut self, u: f32) {
+        // A non-finite sample is dropped and the previous state held
+        if !u.is_finite() {
+            self.healthy = false;
+            return;
+        }
+        self.u[0] = match self.prefilter {
+            PreFilter::None => u,
+            _ => self.prefilter_stage.process(u),
+        };
+
+        // Orthogonal signal generator
+        self.osg_u[0] = self.osg_coeff.b0 * self.u[0]
+            + self.osg_coeff.b2 * self.u[2]
+            + self.osg_coeff.a1 * self.osg_u[1]
+            + self.osg_coeff.a2 * self.osg_u[2];
+        self.osg_u[2] = self.osg_u[1];
+        self.osg_u[1] = self.osg_u[0];
+        self.osg_qu[0] = self.osg_coeff.qb0 * self.u[0]
+            + self.osg_coeff.qb1 * self.u[1]
+            + self.osg_coeff.qb2 * self.u[2]
+            + self.osg_coeff.a1 * self.osg_qu[1]
+            + self.osg_coeff.a2 * self.osg_qu[2];
+        self.osg_qu[2] = self.osg_qu[1];
+        self.osg_qu[1] = self.osg_qu[0];
+        self.u[2] = self.u[1];
+        self.u[1] = self.u[0];
+
+        // Park transform from alpha beta to d-q axis
+        self.u_q[0] = self.cos * self.osg_u[0] + self.sin * self.osg_qu[0];
+        self.u_d[0] = self.cos * self.osg_qu[0] - self.sin * self.osg_u[0];
+
+        // Loop filter
+        self.ylf[0] = -self.lpf_coeff.a1 * self.ylf[1]
+            + self.lpf_coeff.b0 * self.u_q[0]
+            + self.lpf_coeff.b1 * self.u_q[1];
+        if self.fnom + self.ylf[0] > self.fo_max {
+            self.ylf[0] = self.fo_max - self.fnom;
+        } else if self.fnom + self.ylf[0] < self.fo_min {
+            self.ylf[0] = self.fo_min - self.fnom;
+        }
+        self.ylf[1] = self.ylf[0];
+        self.u_q[1] = self.u_q[0];
+
+        // VCO
+        self.fo = self.fnom + self.ylf[0];
+        self.theta[0] = self.theta[1] + self.fo * self.delta_t * TAU;
+        if self.theta[0] > TAU {
+            self.theta[0] -= TAU;
+        } else if self.theta[0] < 0.0 {
+            self.theta[0] += TAU;
+        }
+        self.theta[1] = self.theta[0];
+        self.sin = sin(self.theta[0]);
+        self.cos = cos(self.theta[0]);
+
+        // Free-running phase at the nominal frequency for the warm-up output
+        self.freerun_theta += self.fnom * self.delta_t * TAU;
+        if self.freerun_theta > TAU {
+            self.freerun_theta -= TAU;
+        }
+
+        // Lock detector, the q component tracks the loop error and the SOGI residual catches
+        // input steps the loop has not seen yet, both normalized and smoothed over a cycle
+        let amplitude = sqrt(self.osg_u[0] * self.osg_u[0] + self.osg_qu[0] * self.osg_qu[0]);
+        let residual = if amplitude > 1e-6 {
+            (self.u[0] - self.osg_u[0]).abs() / amplitude
+        } else {
+            0.0
+        };
+        let error = if amplitude > 1e-6 {
+            self.u_q[0].abs() / amplitude + residual
+        } else {
+            1.0
+        };
+        self.lock_error += (self.fnom * self.delta_t).min(1.0) * (error - self.lock_error);
+        if self.lock_error < self.lock_threshold {
+            self.lock_timer += self.delta_t;
+            if self.lock_timer >= self.lock_time {
+                self.locked = true;
+            }
+        } else {
+            self.lock_timer = 0.0;
+            if self.lock_error > 2.0 * self.lock_threshold {
+                self.locked = false;
+            }
+        }
+
+        // Phase jump detector, an abrupt rise of the residual over its baseline of ~5 cycles
+        if self.jump_hold > 0.0 {
+            self.jump_hold -= self.delta_t;
+        }
+        if residual - self.residual_baseline > self.jump_level {
+            self.jump_hold = 1.0 / self.fnom;
+        }
+        self.residual_baseline +=
+            (0.2 * self.fnom * self.delta_t).min(1.0) * (residual - self.residual_baseline);
+
+        // FLL, move the SOGI center frequency towards the estimated frequency
+        if self.fll_enable {
+            self.wn += self.fnom * self.delta_t * (TAU * self.fo - self.wn);
+            self.coeff_update();
+        }
+
+        // Any non-finite state propagates into this sum, restart rather than stay poisoned
+        if (self.osg_u[0] + self.osg_qu[0] + self.ylf[0] + self.wn).is_finite() {
+            self.healthy = true;
+        } else {
+            self.healthy = false;
+            self.reset();
+        }
+    }
+    pub fn is_healthy(&self) -> bool {
+        self.healthy
+    }
+    pub fn get_theta(&self) -> f32 {
+        self.theta[0]
+    }
+    pub fn phase_jump_detected(&self) -> bool {
+        self.jump_hold > 0.0
+    }
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+    /* Output phase, the free-running phase during warm-up when enabled */
+    pub fn get_phase(&self) -> f32 {
+        if self.warmup_freerun && !self.locked {
+            self.freerun_theta
+        } else {
+            self.theta[0]
+        }
+    }
+    pub fn get_phase_or_none(&self) -> Option<f32> {
+        if self.locked {
+            Some(self.theta[0])
+        } else {
+            None
+        }
+    }
+    pub fn get_frequency(&self) -> f32 {
+        self.fo
+    }
+    pub fn get_sin(&self) -> f32 {
+        self.sin
+    }
+    pub fn get_cos(&self) -> f32 {
+        self.cos
+    }
+    pub fn get_osg_u(&self) -> f32 {
+        self.osg_u[0]
+    }
+    pub fn get_osg_qu(&self) -> f32 {
+        self.osg_qu[0]
     }
-    pub fn run(&mut self) {
-        // TODO
+    pub fn get_center_frequency(&self) -> f32 {
+        self.wn / TAU
     }
 }
@@ -0,0 +1,64 @@
+pub struct AdcChannel {
+    offset: f32, /* Raw reading at zero engineering units, counts */
+    gain: f32,   /* Engineering units per count */
+    min: f32,    /* Lower clamp in engineering units */
+    max: f32,    /* Upper clamp in engineering units */
+}
+
+impl AdcChannel {
+    pub fn new(offset: f32, gain: f32) -> AdcChannel {
+        AdcChannel {
+            offset,
+            gain,
+            min: f32::NEG_INFINITY,
+            max: f32::INFINITY,
+        }
+    }
+    /* Solves offset and gain from two readings, None when the points do not define a slope */
+    pub fn from_two_points(point1: (u16, f32), point2: (u16, f32)) -> Option<AdcChannel> {
+        let (raw1, eng1) = point1;
+        let (raw2, eng2) = point2;
+        if raw1 == raw2 || eng1 == eng2 {
+            return None;
+        }
+        let gain = (eng2 - eng1) / (raw2 as f32 - raw1 as f32);
+        Some(AdcChannel::new(raw1 as f32 - eng1 / gain, gain))
+    }
+    /* Applied after scaling, in engineering units */
+    pub fn set_clamp(&mut self, min: f32, max: f32) {
+        self.min = min;
+        self.max = max;
+    }
+    pub fn set_offset(&mut self, offset: f32) {
+        self.offset = offset;
+    }
+    pub fn get_offset(&self) -> f32 {
+        self.offset
+    }
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+    pub fn get_gain(&self) -> f32 {
+        self.gain
+    }
+    /* Sets the offset to the mean of readings taken at zero input, an empty slice is ignored */
+    pub fn calibrate_offset(&mut self, samples: &[u16]) {
+        if samples.is_empty() {
+            return;
+        }
+        // Integer sum, exact for any slice length a u64 can count
+        let sum: u64 = samples.iter().map(|&s| s as u64).sum();
+        self.offset = (sum as f64 / samples.len() as f64) as f32;
+    }
+    pub fn convert(&self, raw: u16) -> f32 {
+        // Every u16 is exact in f32, so the subtraction is done in float and can go negative
+        let value = (raw as f32 - self.offset) * self.gain;
+        if value > self.max {
+            self.max
+        } else if value < self.min {
+            self.min
+        } else {
+            value
+        }
+    }
+}
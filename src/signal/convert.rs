@@ -0,0 +1,107 @@
+use crate::math::float::sin;
+use core::f32::consts::{FRAC_1_SQRT_2, SQRT_2};
+
+/* A crest factor below 1 is not physical, the peak can never be below the RMS */
+fn valid_crest(crest_factor: f32) -> f32 {
+    if crest_factor < 1.0 {
+        1.0
+    } else {
+        crest_factor
+    }
+}
+
+/* Sinusoidal peak from RMS */
+pub fn rms_to_peak(rms: f32) -> f32 {
+    SQRT_2 * rms
+}
+
+/* Sinusoidal RMS from peak */
+pub fn peak_to_rms(peak: f32) -> f32 {
+    FRAC_1_SQRT_2 * peak
+}
+
+/* sqrt(2) * rms * sin(phase) */
+pub fn instantaneous_reference(rms: f32, phase: f32) -> f32 {
+    SQRT_2 * rms * sin(phase)
+}
+
+/* Peak of any waveform from its RMS and crest factor (peak / RMS, sqrt(2) for a sine,
+1 for a square wave, sqrt(3) for a triangle) */
+pub fn rms_to_peak_with_crest(rms: f32, crest_factor: f32) -> f32 {
+    valid_crest(crest_factor) * rms
+}
+
+pub fn peak_to_rms_with_crest(peak: f32, crest_factor: f32) -> f32 {
+    peak / valid_crest(crest_factor)
+}
+
+/* Scales a unit-peak waveform sample in [-1, 1] so the waveform has the given RMS */
+pub fn instantaneous_reference_with_crest(rms: f32, crest_factor: f32, unit_sample: f32) -> f32 {
+    valid_crest(crest_factor) * rms * unit_sample
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::float::sqrt;
+    use core::f32::consts::TAU;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() <= 1e-4 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn sine_helpers_round_trip() {
+        assert!(close(rms_to_peak(230.0), 325.269));
+        assert!(close(peak_to_rms(325.269), 230.0));
+        assert!(close(peak_to_rms(rms_to_peak(12.5)), 12.5));
+    }
+
+    #[test]
+    fn instantaneous_reference_has_the_requested_rms() {
+        let samples = 400;
+        let mut sum_sq = 0.0;
+        let mut peak = 0.0f32;
+        for n in 0..samples {
+            let value = instantaneous_reference(230.0, TAU * n as f32 / samples as f32);
+            sum_sq += value * value;
+            peak = peak.max(value.abs());
+        }
+        assert!(close(sqrt(sum_sq / samples as f32), 230.0));
+        assert!(close(peak, rms_to_peak(230.0)));
+        assert!(close(
+            instantaneous_reference(230.0, 0.25 * TAU),
+            rms_to_peak(230.0)
+        ));
+    }
+
+    #[test]
+    fn sine_crest_factor_matches_the_sine_helpers() {
+        let crest = SQRT_2;
+        assert!(close(
+            rms_to_peak_with_crest(230.0, crest),
+            rms_to_peak(230.0)
+        ));
+        assert!(close(
+            peak_to_rms_with_crest(325.0, crest),
+            peak_to_rms(325.0)
+        ));
+        for n in 0..16 {
+            let phase = TAU * n as f32 / 16.0;
+            assert!(close(
+                instantaneous_reference_with_crest(230.0, crest, sin(phase)),
+                instantaneous_reference(230.0, phase)
+            ));
+        }
+    }
+
+    #[test]
+    fn unit_crest_factor_is_a_square_wave() {
+        assert_eq!(rms_to_peak_with_crest(48.0, 1.0), 48.0);
+        assert_eq!(peak_to_rms_with_crest(48.0, 1.0), 48.0);
+        assert_eq!(instantaneous_reference_with_crest(48.0, 1.0, 1.0), 48.0);
+        assert_eq!(instantaneous_reference_with_crest(48.0, 1.0, -1.0), -48.0);
+        // Crest factors below 1 are treated as 1
+        assert_eq!(rms_to_peak_with_crest(48.0, 0.5), 48.0);
+    }
+}
@@ -0,0 +1,99 @@
+pub struct DelayLine<const N: usize> {
+    buffer: [f32; N], /* Last N input samples */
+    write: usize,     /* Slot the next sample is written to */
+    delay: usize,     /* Delay in samples, at most N */
+}
+
+impl<const N: usize> Default for DelayLine<N> {
+    fn default() -> Self {
+        DelayLine::new()
+    }
+}
+
+impl<const N: usize> DelayLine<N> {
+    pub fn new() -> DelayLine<N> {
+        DelayLine {
+            buffer: [0.0; N],
+            write: 0,
+            delay: N,
+        }
+    }
+    /* Clamped to N, taps that have not been written yet read as zero */
+    pub fn set_delay(&mut self, samples: usize) {
+        self.delay = if samples > N { N } else { samples };
+    }
+    pub fn get_delay(&self) -> usize {
+        self.delay
+    }
+    pub fn reset(&mut self) {
+        self.buffer = [0.0; N];
+        self.write = 0;
+    }
+    /* Sample pushed `delay` calls ago, 0 being the current input */
+    fn tap(&self, delay: usize, input: f32) -> f32 {
+        if delay == 0 {
+            input
+        } else {
+            self.buffer[(self.write + N - delay) % N]
+        }
+    }
+    fn store(&mut self, input: f32) {
+        if N > 0 {
+            self.buffer[self.write] = input;
+            self.write = (self.write + 1) % N;
+        }
+    }
+    pub fn push(&mut self, sample: f32) -> f32 {
+        let output = self.tap(self.delay, sample);
+        self.store(sample);
+        output
+    }
+}
+
+pub struct FractionalDelay<const N: usize> {
+    line: DelayLine<N>, /* Integer-sample history */
+    delay: f32,         /* Delay in samples, within [0, N] */
+}
+
+impl<const N: usize> Default for FractionalDelay<N> {
+    fn default() -> Self {
+        FractionalDelay::new()
+    }
+}
+
+impl<const N: usize> FractionalDelay<N> {
+    pub fn new() -> FractionalDelay<N> {
+        FractionalDelay {
+            line: DelayLine::new(),
+            delay: N as f32,
+        }
+    }
+    pub fn set_delay(&mut self, samples: f32) {
+        self.delay = if samples < 0.0 {
+            0.0
+        } else if samples > N as f32 {
+            N as f32
+        } else {
+            samples
+        };
+    }
+    pub fn get_delay(&self) -> f32 {
+        self.delay
+    }
+    pub fn reset(&mut self) {
+        self.line.reset();
+    }
+    /* Linear interpolation between the two integer taps around the delay */
+    pub fn push(&mut self, sample: f32) -> f32 {
+        let whole = self.delay as usize;
+        let frac = self.delay - whole as f32;
+        let a = self.line.tap(whole, sample);
+        let output = if frac > 0.0 {
+            a + frac * (self.line.tap(whole + 1, sample) - a)
+        } else {
+            a
+        };
+        self.line.store(sample);
+        output
+    }
+}
@@ -0,0 +1,64 @@
+use crate::math::float::{cos, sin, sqrt};
+use core::f32::consts::TAU;
+
+struct Radix2<const N: usize>;
+
+impl<const N: usize> Radix2<N> {
+    const POWER_OF_TWO: () = assert!(N.is_power_of_two(), "FFT length must be a power of two");
+}
+
+/* In-place iterative Cooley-Tukey FFT, N must be a power of two */
+pub fn fft_radix2<const N: usize>(real: &mut [f32; N], imag: &mut [f32; N]) {
+    let () = Radix2::<N>::POWER_OF_TWO;
+    if N < 2 {
+        return;
+    }
+
+    // Bit-reversal permutation
+    let bits = N.trailing_zeros();
+    for i in 0..N {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            real.swap(i, j);
+            imag.swap(i, j);
+        }
+    }
+
+    // Butterflies, twiddles are evaluated directly to avoid recurrence drift
+    let mut len = 2;
+    while len <= N {
+        let half = len / 2;
+        for j in 0..half {
+            let angle = -TAU * j as f32 / len as f32;
+            let (w_re, w_im) = (cos(angle), sin(angle));
+            let mut start = 0;
+            while start < N {
+                let a = start + j;
+                let b = a + half;
+                let t_re = real[b] * w_re - imag[b] * w_im;
+                let t_im = real[b] * w_im + imag[b] * w_re;
+                real[b] = real[a] - t_re;
+                imag[b] = imag[a] - t_im;
+                real[a] += t_re;
+                imag[a] += t_im;
+                start += len;
+            }
+        }
+        len *= 2;
+    }
+}
+
+/* Single-sided amplitude spectrum for bins 0..=N/2. Bins 1..N/2-1 are doubled to fold in the
+negative frequencies, DC and Nyquist have no mirror image and are not. */
+pub fn magnitude_spectrum<const N: usize>(real: &[f32; N], imag: &[f32; N], out: &mut [f32]) {
+    let () = Radix2::<N>::POWER_OF_TWO;
+    let nyquist = N / 2;
+    for (k, amplitude) in out.iter_mut().enumerate().take(nyquist + 1) {
+        let magnitude = sqrt(real[k] * real[k] + imag[k] * imag[k]) / N as f32;
+        *amplitude = if k == 0 || k == nyquist {
+            magnitude
+        } else {
+            2.0 * magnitude
+        };
+    }
+}
@@ -0,0 +1,88 @@
+use crate::math::float::{atan2, cos, sin, sqrt};
+use core::f32::consts::TAU;
+
+/* Second-order section in transposed direct form II, a0 normalized to 1 */
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    s1: f32, /* First state */
+    s2: f32, /* Second state */
+}
+
+impl Biquad {
+    pub fn new(b0: f32, b1: f32, b2: f32, a1: f32, a2: f32) -> Biquad {
+        Biquad {
+            b0,
+            b1,
+            b2,
+            a1,
+            a2,
+            s1: 0.0,
+            s2: 0.0,
+        }
+    }
+    /* Unity gain pass-through */
+    pub fn identity() -> Biquad {
+        Biquad::new(1.0, 0.0, 0.0, 0.0, 0.0)
+    }
+    /* Notch at freq with quality factor q, unity gain away from the notch */
+    pub fn notch(freq: f32, q: f32, sample_rate: f32) -> Biquad {
+        let w0 = TAU * freq / sample_rate;
+        let alpha = sin(w0) / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        let c = -2.0 * cos(w0) / a0;
+        Biquad::new(1.0 / a0, c, 1.0 / a0, c, (1.0 - alpha) / a0)
+    }
+    /* Second-order low-pass with cutoff freq and quality factor q */
+    pub fn lowpass(freq: f32, q: f32, sample_rate: f32) -> Biquad {
+        let w0 = TAU * freq / sample_rate;
+        let alpha = sin(w0) / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        let b = (1.0 - cos(w0)) / a0;
+        Biquad::new(0.5 * b, b, 0.5 * b, -2.0 * cos(w0) / a0, (1.0 - alpha) / a0)
+    }
+    /* First-order high-pass (DC blocker) with cutoff freq, bilinear transform */
+    pub fn dc_block(cutoff: f32, sample_rate: f32) -> Biquad {
+        let half = 0.5 * TAU * cutoff / sample_rate;
+        let k = sin(half) / cos(half);
+        let b0 = 1.0 / (1.0 + k);
+        Biquad::new(b0, -b0, 0.0, (k - 1.0) / (1.0 + k), 0.0)
+    }
+    pub fn reset(&mut self) {
+        self.s1 = 0.0;
+        self.s2 = 0.0;
+    }
+    pub fn process(&mut self, input: f32) -> f32 {
+        let out = self.b0 * input + self.s1;
+        self.s1 = self.b1 * input - self.a1 * out + self.s2;
+        self.s2 = self.b2 * input - self.a2 * out;
+        out
+    }
+    /* Complex response at freq as (real, imaginary) */
+    fn response(&self, freq: f32, sample_rate: f32) -> (f32, f32) {
+        let w = TAU * freq / sample_rate;
+        let (c1, s1, c2, s2) = (cos(w), sin(w), cos(2.0 * w), sin(2.0 * w));
+        let (nr, ni) = (
+            self.b0 + self.b1 * c1 + self.b2 * c2,
+            -self.b1 * s1 - self.b2 * s2,
+        );
+        let (dr, di) = (
+            1.0 + self.a1 * c1 + self.a2 * c2,
+            -self.a1 * s1 - self.a2 * s2,
+        );
+        let den = dr * dr + di * di;
+        ((nr * dr + ni * di) / den, (ni * dr - nr * di) / den)
+    }
+    pub fn gain_at(&self, freq: f32, sample_rate: f32) -> f32 {
+        let (re, im) = self.response(freq, sample_rate);
+        sqrt(re * re + im * im)
+    }
+    /* Phase shift at freq in radians, positive for a lead */
+    pub fn phase_at(&self, freq: f32, sample_rate: f32) -> f32 {
+        let (re, im) = self.response(freq, sample_rate);
+        atan2(im, re)
+    }
+}
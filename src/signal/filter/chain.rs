@@ -0,0 +1,80 @@
+use super::biquad::Biquad;
+use super::iir::IIRFilter;
+use super::kalman::KalmanFilter;
+
+pub enum FilterStage {
+    Biquad(Biquad),
+    Iir(IIRFilter),
+    Kalman(KalmanFilter),
+}
+
+impl FilterStage {
+    pub fn process(&mut self, input: f32) -> f32 {
+        match self {
+            FilterStage::Biquad(stage) => stage.process(input),
+            FilterStage::Iir(stage) => {
+                stage.calculate(input);
+                stage.get_out()
+            }
+            FilterStage::Kalman(stage) => {
+                stage.update(input);
+                stage.get_output_estimate()
+            }
+        }
+    }
+    pub fn reset(&mut self) {
+        match self {
+            FilterStage::Biquad(stage) => stage.reset(),
+            FilterStage::Iir(stage) => stage.reset(),
+            FilterStage::Kalman(stage) => stage.reset(),
+        }
+    }
+}
+
+/* Up to N stages run in the order they were pushed */
+pub struct FilterChain<const N: usize> {
+    stages: [Option<FilterStage>; N],
+    len: usize,
+}
+
+impl<const N: usize> Default for FilterChain<N> {
+    fn default() -> Self {
+        FilterChain::new()
+    }
+}
+
+impl<const N: usize> FilterChain<N> {
+    pub fn new() -> FilterChain<N> {
+        FilterChain {
+            stages: core::array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+    /* Returns false when the chain is full */
+    pub fn push(&mut self, stage: FilterStage) -> bool {
+        if self.len >= N {
+            return false;
+        }
+        self.stages[self.len] = Some(stage);
+        self.len += 1;
+        true
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn process(&mut self, sample: f32) -> f32 {
+        let mut value = sample;
+        for stage in self.stages.iter_mut().take(self.len).flatten() {
+            value = stage.process(value);
+        }
+        value
+    }
+    pub fn reset(&mut self) {
+        for stage in self.stages.iter_mut().take(self.len).flatten() {
+            stage.reset();
+        }
+    }
+}
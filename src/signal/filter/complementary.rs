@@ -0,0 +1,64 @@
+use crate::math::float::{wrap_2pi, wrap_pi};
+use core::f32::consts::TAU;
+
+/* First-order complementary filter, the high-frequency input passes a high-pass and the
+low-frequency input the matching low-pass, both with the crossover as corner */
+pub struct ComplementaryFilter {
+    alpha: f32,     /* tau / (tau + dt), weight of the high-frequency path */
+    high_prev: f32, /* Previous high-frequency input */
+    out: f32,       /* Last fused output */
+    primed: bool,   /* The state has been seeded from a first sample */
+}
+
+impl ComplementaryFilter {
+    pub fn new(crossover_hz: f32, sample_rate: f32) -> ComplementaryFilter {
+        let mut filter = ComplementaryFilter {
+            alpha: 0.0,
+            high_prev: 0.0,
+            out: 0.0,
+            primed: false,
+        };
+        filter.set_crossover(crossover_hz, sample_rate);
+        filter
+    }
+    pub fn set_crossover(&mut self, crossover_hz: f32, sample_rate: f32) {
+        let tau = 1.0 / (TAU * crossover_hz);
+        self.alpha = tau / (tau + 1.0 / sample_rate);
+    }
+    pub fn reset(&mut self) {
+        self.high_prev = 0.0;
+        self.out = 0.0;
+        self.primed = false;
+    }
+    /* The first call starts from the low-frequency input */
+    pub fn fuse(&mut self, high_freq_signal: f32, low_freq_signal: f32) -> f32 {
+        if !self.primed {
+            self.primed = true;
+            self.high_prev = high_freq_signal;
+            self.out = low_freq_signal;
+            return self.out;
+        }
+        // Propagate with the high-frequency increment, then correct towards the reference
+        let predicted = self.out + (high_freq_signal - self.high_prev);
+        self.high_prev = high_freq_signal;
+        self.out = predicted + (1.0 - self.alpha) * (low_freq_signal - predicted);
+        self.out
+    }
+    /* fuse for angles in radians, increments and corrections are taken on the short way
+    around the circle and the output is wrapped into [0, 2*PI) */
+    pub fn fuse_angle(&mut self, high_freq_angle: f32, low_freq_angle: f32) -> f32 {
+        if !self.primed {
+            self.primed = true;
+            self.high_prev = high_freq_angle;
+            self.out = wrap_2pi(low_freq_angle);
+            return self.out;
+        }
+        let predicted = self.out + wrap_pi(high_freq_angle - self.high_prev);
+        self.high_prev = high_freq_angle;
+        self.out = wrap_2pi(predicted + (1.0 - self.alpha) * wrap_pi(low_freq_angle - predicted));
+        self.out
+    }
+    pub fn get_out(&self) -> f32 {
+        self.out
+    }
+}
@@ -16,6 +16,9 @@ impl IIRFilter {
     pub fn calculate(&mut self, input: f32) {
         self.out = self.alpha * input + (1.0 - self.alpha) * self.out;
     }
+    pub fn reset(&mut self) {
+        self.out = 0.0;
+    }
     pub fn get_out(&self) -> f32 {
         self.out
     }
@@ -24,6 +24,15 @@ impl KalmanFilter {
             r: r,
         }
     }
+    pub fn reset(&mut self) {
+        self.x_est = 0.0;
+        self.x_est_last = 0.0;
+        self.x_temp_est = 0.0;
+        self.p = 0.0;
+        self.p_temp = 0.0;
+        self.p_last = 0.0;
+        self.k = 0.0;
+    }
     pub fn get_output_estimate(&self) -> f32 {
         self.x_est
     }
@@ -37,3 +46,160 @@ impl KalmanFilter {
         self.x_est_last = self.x_est;
     }
 }
+
+/* Scalar linear Kalman filter for x[k+1] = a x[k] + b u[k] + w, z = x + v */
+pub struct KalmanFilter1D {
+    x: f32, /* State estimate */
+    p: f32, /* Estimate variance */
+    a: f32, /* State transition */
+    b: f32, /* Control input gain */
+    q: f32, /* Process noise variance */
+    r: f32, /* Measurement noise variance */
+}
+
+impl KalmanFilter1D {
+    pub fn new(process_noise: f32, measurement_noise: f32) -> KalmanFilter1D {
+        KalmanFilter1D {
+            x: 0.0,
+            p: 1.0,
+            a: 1.0,
+            b: 1.0,
+            q: process_noise,
+            r: measurement_noise,
+        }
+    }
+    pub fn set_model(&mut self, a: f32, b: f32) {
+        self.a = a;
+        self.b = b;
+    }
+    pub fn set_state(&mut self, estimate: f32, variance: f32) {
+        self.x = estimate;
+        self.p = variance;
+    }
+    pub fn predict(&mut self, control_input: f32) {
+        self.x = self.a * self.x + self.b * control_input;
+        self.p = self.a * self.p * self.a + self.q;
+    }
+    pub fn update(&mut self, measurement: f32) -> f32 {
+        let k = self.p / (self.p + self.r);
+        self.x += k * (measurement - self.x);
+        // Joseph form keeps the variance positive under rounding
+        self.p = (1.0 - k) * (1.0 - k) * self.p + k * k * self.r;
+        self.x
+    }
+    pub fn get_estimate(&self) -> f32 {
+        self.x
+    }
+    pub fn get_variance(&self) -> f32 {
+        self.p
+    }
+}
+
+fn dot<const N: usize>(a: &[f32; N], b: &[f32; N]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+/* A B */
+fn mat_mul<const N: usize>(a: &[[f32; N]; N], b: &[[f32; N]; N]) -> [[f32; N]; N] {
+    let mut out = [[0.0; N]; N];
+    for (out_row, a_row) in out.iter_mut().zip(a.iter()) {
+        for (a_ik, b_row) in a_row.iter().zip(b.iter()) {
+            for (o, b_kj) in out_row.iter_mut().zip(b_row.iter()) {
+                *o += a_ik * b_kj;
+            }
+        }
+    }
+    out
+}
+
+/* A B^T + C */
+fn mat_mul_transpose_add<const N: usize>(
+    a: &[[f32; N]; N],
+    b: &[[f32; N]; N],
+    c: &[[f32; N]; N],
+) -> [[f32; N]; N] {
+    let mut out = *c;
+    for (out_row, a_row) in out.iter_mut().zip(a.iter()) {
+        for (o, b_row) in out_row.iter_mut().zip(b.iter()) {
+            *o += dot(a_row, b_row);
+        }
+    }
+    out
+}
+
+/* N-state linear Kalman filter with a scalar control input and a scalar measurement,
+x[k+1] = F x[k] + B u[k] + w, z = H x + v */
+pub struct KalmanFilterND<const N: usize> {
+    x: [f32; N],      /* State estimate */
+    p: [[f32; N]; N], /* Estimate covariance */
+    f: [[f32; N]; N], /* State transition */
+    b: [f32; N],      /* Control input gain */
+    h: [f32; N],      /* Observation row */
+    q: [[f32; N]; N], /* Process noise covariance */
+    r: f32,           /* Measurement noise variance */
+}
+
+impl<const N: usize> KalmanFilterND<N> {
+    pub fn new(f: [[f32; N]; N], h: [f32; N], q: [[f32; N]; N], r: f32) -> KalmanFilterND<N> {
+        let mut p = [[0.0; N]; N];
+        for (i, row) in p.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        KalmanFilterND {
+            x: [0.0; N],
+            p,
+            f,
+            b: [0.0; N],
+            h,
+            q,
+            r,
+        }
+    }
+    pub fn set_control(&mut self, b: [f32; N]) {
+        self.b = b;
+    }
+    pub fn set_state(&mut self, x: [f32; N], p: [[f32; N]; N]) {
+        self.x = x;
+        self.p = p;
+    }
+    pub fn predict(&mut self, control_input: f32) {
+        let mut x = [0.0; N];
+        for ((xi, f_row), bi) in x.iter_mut().zip(self.f.iter()).zip(self.b.iter()) {
+            *xi = dot(f_row, &self.x) + bi * control_input;
+        }
+        self.x = x;
+        // P = F P F^T + Q
+        self.p = mat_mul_transpose_add(&mat_mul(&self.f, &self.p), &self.f, &self.q);
+    }
+    pub fn update(&mut self, measurement: f32) -> [f32; N] {
+        // Gain K = P H^T / (H P H^T + R)
+        let mut k = [0.0; N];
+        for (ki, p_row) in k.iter_mut().zip(self.p.iter()) {
+            *ki = dot(p_row, &self.h);
+        }
+        let s = dot(&self.h, &k) + self.r;
+        let innovation = measurement - dot(&self.h, &self.x);
+        for (xi, ki) in self.x.iter_mut().zip(k.iter_mut()) {
+            *ki /= s;
+            *xi += *ki * innovation;
+        }
+
+        // Joseph form, P = (I - K H) P (I - K H)^T + K R K^T
+        let mut a = [[0.0; N]; N];
+        let mut krk = [[0.0; N]; N];
+        for (i, (a_row, krk_row)) in a.iter_mut().zip(krk.iter_mut()).enumerate() {
+            for (j, (a_ij, krk_ij)) in a_row.iter_mut().zip(krk_row.iter_mut()).enumerate() {
+                *a_ij = if i == j { 1.0 } else { 0.0 } - k[i] * self.h[j];
+                *krk_ij = k[i] * self.r * k[j];
+            }
+        }
+        self.p = mat_mul_transpose_add(&mat_mul(&a, &self.p), &a, &krk);
+        self.x
+    }
+    pub fn get_state(&self) -> [f32; N] {
+        self.x
+    }
+    pub fn get_covariance(&self) -> [[f32; N]; N] {
+        self.p
+    }
+}
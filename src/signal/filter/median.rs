@@ -0,0 +1,98 @@
+pub struct MedianFilter<const N: usize> {
+    history: [f32; N], /* Samples in arrival order, ring buffer */
+    sorted: [f32; N],  /* The same samples kept in ascending order */
+    index: usize,      /* Slot of the oldest sample in history */
+    count: usize,      /* Samples held, N once the window has filled */
+    out: f32,          /* Last median */
+}
+
+impl<const N: usize> Default for MedianFilter<N> {
+    fn default() -> Self {
+        MedianFilter::new()
+    }
+}
+
+impl<const N: usize> MedianFilter<N> {
+    const ODD_WINDOW: () = assert!(N % 2 == 1, "MedianFilter window length must be odd");
+
+    pub fn new() -> MedianFilter<N> {
+        let () = MedianFilter::<N>::ODD_WINDOW;
+        MedianFilter {
+            history: [0.0; N],
+            sorted: [0.0; N],
+            index: 0,
+            count: 0,
+            out: 0.0,
+        }
+    }
+    pub fn reset(&mut self) {
+        self.history = [0.0; N];
+        self.sorted = [0.0; N];
+        self.index = 0;
+        self.count = 0;
+        self.out = 0.0;
+    }
+    /* Worst case is one pass over the window to drop the oldest sample and one to insert.
+    Non-finite samples cannot be ordered or evicted reliably, they are dropped and the last
+    median is returned. */
+    pub fn process(&mut self, sample: f32) -> f32 {
+        if !sample.is_finite() {
+            return self.out;
+        }
+        let mut len = self.count;
+        if len == N {
+            // Drop the oldest sample from the sorted buffer
+            let oldest = self.history[self.index];
+            let mut pos = 0;
+            while pos < len - 1 && self.sorted[pos] != oldest {
+                pos += 1;
+            }
+            for i in pos..len - 1 {
+                self.sorted[i] = self.sorted[i + 1];
+            }
+            len -= 1;
+        } else {
+            self.count += 1;
+        }
+
+        // Insertion from the top, shifting larger samples up by one
+        let mut pos = len;
+        while pos > 0 && self.sorted[pos - 1] > sample {
+            self.sorted[pos] = self.sorted[pos - 1];
+            pos -= 1;
+        }
+        self.sorted[pos] = sample;
+        self.history[self.index] = sample;
+        self.index = (self.index + 1) % N;
+
+        // While the window fills an even count averages the two middle samples
+        let n = self.count;
+        self.out = if n % 2 == 1 {
+            self.sorted[n / 2]
+        } else {
+            0.5 * (self.sorted[n / 2 - 1] + self.sorted[n / 2])
+        };
+        self.out
+    }
+    pub fn get_out(&self) -> f32 {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_finite_samples_do_not_enter_the_window() {
+        let mut filter = MedianFilter::<3>::new();
+        filter.process(1.0);
+        assert_eq!(filter.process(f32::NAN), 1.0);
+        assert_eq!(filter.process(f32::INFINITY), 1.0);
+        let mut out = 0.0;
+        for sample in [2.0, 3.0, 4.0, 5.0, 6.0] {
+            out = filter.process(sample);
+        }
+        assert_eq!(out, 5.0);
+    }
+}
@@ -1,3 +1,9 @@
+pub mod biquad;
+pub mod chain;
+pub mod complementary;
 pub mod fir;
 pub mod iir;
 pub mod kalman;
+pub mod median;
+pub mod moving_average;
+pub mod ripple_notch;
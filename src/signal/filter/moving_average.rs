@@ -0,0 +1,64 @@
+pub struct MovingAverage<const N: usize> {
+    buffer: [f32; N], /* Samples in the window */
+    index: usize,     /* Slot of the oldest sample */
+    count: usize,     /* Samples held, N once the window has filled */
+    sum: f32,         /* Running sum of the window */
+    fresh_sum: f32,   /* Sum rebuilt over the current pass of the ring buffer */
+    out: f32,         /* Last average */
+}
+
+impl<const N: usize> Default for MovingAverage<N> {
+    fn default() -> Self {
+        MovingAverage::new()
+    }
+}
+
+impl<const N: usize> MovingAverage<N> {
+    pub fn new() -> MovingAverage<N> {
+        MovingAverage {
+            buffer: [0.0; N],
+            index: 0,
+            count: 0,
+            sum: 0.0,
+            fresh_sum: 0.0,
+            out: 0.0,
+        }
+    }
+    pub fn reset(&mut self) {
+        self.buffer = [0.0; N];
+        self.index = 0;
+        self.count = 0;
+        self.sum = 0.0;
+        self.fresh_sum = 0.0;
+        self.out = 0.0;
+    }
+    pub fn is_full(&self) -> bool {
+        self.count >= N
+    }
+    pub fn process(&mut self, sample: f32) -> f32 {
+        if N == 0 {
+            self.out = sample;
+            return sample;
+        }
+        self.sum += sample - self.buffer[self.index];
+        self.fresh_sum += sample;
+        self.buffer[self.index] = sample;
+        self.index += 1;
+        if self.count < N {
+            self.count += 1;
+        }
+        if self.index >= N {
+            // Every slot has been rewritten since the last wrap, so the fresh sum is exact and
+            // replaces the running sum to stop rounding errors from accumulating
+            self.index = 0;
+            self.sum = self.fresh_sum;
+            self.fresh_sum = 0.0;
+        }
+        // Average over the samples received so far while the window fills
+        self.out = self.sum / self.count as f32;
+        self.out
+    }
+    pub fn get_out(&self) -> f32 {
+        self.out
+    }
+}
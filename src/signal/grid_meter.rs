@@ -0,0 +1,97 @@
+use crate::math::float::sqrt;
+use core::f32::consts::SQRT_2;
+
+pub struct GridMeter<const N: usize> {
+    buffer: [f32; N],   /* Squared samples of the last cycle */
+    window: usize,      /* Samples per nominal cycle, at most N */
+    index: usize,       /* Next write position in the buffer */
+    filled: usize,      /* Valid samples in the buffer while starting up */
+    sum_sq: f32,        /* Running sum of the squared samples in the window */
+    fresh_sum_sq: f32,  /* Sum rebuilt from scratch every window to cancel running-sum drift */
+    rms: f32,           /* One-cycle RMS */
+    sample_rate: f32,   /* Frequency of calling update */
+    frequency: f32,     /* Frequency from the last zero-crossing period */
+    hysteresis: f32,    /* Zero-crossing arming band as a fraction of the peak */
+    armed: bool,        /* Signal has been below the negative band since the last crossing */
+    prev: f32,          /* Previous sample */
+    samples_since: u32, /* Samples since the last rising zero crossing */
+    last_fraction: f32, /* Sub-sample position of the last rising zero crossing */
+    cycle_count: u32,   /* Rising zero crossings detected */
+}
+
+impl<const N: usize> GridMeter<N> {
+    pub fn new(nominal_freq: f32, sample_rate: f32) -> GridMeter<N> {
+        let mut window = (sample_rate / nominal_freq + 0.5) as usize;
+        if window > N {
+            window = N;
+        }
+        if window < 1 {
+            window = 1;
+        }
+        GridMeter {
+            buffer: [0.0; N],
+            window,
+            index: 0,
+            filled: 0,
+            sum_sq: 0.0,
+            fresh_sum_sq: 0.0,
+            rms: 0.0,
+            sample_rate,
+            frequency: nominal_freq,
+            hysteresis: 0.05,
+            armed: false,
+            prev: 0.0,
+            samples_since: 0,
+            last_fraction: 0.0,
+            cycle_count: 0,
+        }
+    }
+    pub fn set_hysteresis(&mut self, fraction_of_peak: f32) {
+        self.hysteresis = fraction_of_peak;
+    }
+    pub fn update(&mut self, voltage_sample: f32) {
+        // One-cycle sliding RMS
+        let sq = voltage_sample * voltage_sample;
+        self.sum_sq += sq - self.buffer[self.index];
+        self.fresh_sum_sq += sq;
+        self.buffer[self.index] = sq;
+        self.index += 1;
+        if self.index >= self.window {
+            self.index = 0;
+            self.sum_sq = self.fresh_sum_sq;
+            self.fresh_sum_sq = 0.0;
+        }
+        if self.filled < self.window {
+            self.filled += 1;
+        }
+        let mean_sq = self.sum_sq / self.filled as f32;
+        self.rms = if mean_sq > 0.0 { sqrt(mean_sq) } else { 0.0 };
+
+        // Rising zero crossing, armed only after leaving the negative hysteresis band
+        self.samples_since += 1;
+        let band = self.hysteresis * SQRT_2 * self.rms;
+        if voltage_sample < -band {
+            self.armed = true;
+        } else if self.armed && voltage_sample >= 0.0 && self.prev < 0.0 {
+            self.armed = false;
+            let fraction = self.prev / (self.prev - voltage_sample);
+            if self.cycle_count > 0 {
+                let period = self.samples_since as f32 + fraction - self.last_fraction;
+                self.frequency = self.sample_rate / period;
+            }
+            self.last_fraction = fraction;
+            self.samples_since = 0;
+            self.cycle_count += 1;
+        }
+        self.prev = voltage_sample;
+    }
+    pub fn get_rms(&self) -> f32 {
+        self.rms
+    }
+    pub fn get_frequency(&self) -> f32 {
+        self.frequency
+    }
+    pub fn get_cycle_count(&self) -> u32 {
+        self.cycle_count
+    }
+}
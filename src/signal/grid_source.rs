@@ -0,0 +1,111 @@
+use crate::math::float::{sin, wrap_2pi};
+use core::f32::consts::TAU;
+
+const MAX_HARMONICS: usize = 8;
+
+#[derive(Clone, Copy)]
+struct Harmonic {
+    order: u32,     /* Multiple of the fundamental */
+    amplitude: f32, /* Peak amplitude */
+    phase: f32,     /* Phase relative to the fundamental in radians */
+}
+
+pub struct GridSource {
+    frequency: f32,                       /* Instantaneous fundamental frequency */
+    ramp_target: f32,                     /* Frequency the ramp is heading to */
+    ramp_rate: f32,                       /* Frequency slew in Hz/s, 0 for none */
+    amplitude: f32,                       /* Fundamental peak amplitude */
+    sample_time: f32,                     /* 1/Frequency of calling next */
+    theta: f32,                           /* Fundamental phase accumulator */
+    harmonics: [Harmonic; MAX_HARMONICS], /* Superimposed harmonics */
+    harmonic_count: usize,                /* Harmonics in use */
+    sag_depth: f32,                       /* Fractional amplitude reduction during a sag */
+    sag_remaining: f32,                   /* Time left in the active sag */
+}
+
+impl GridSource {
+    pub fn new(fundamental_freq: f32, amplitude: f32, sample_rate: f32) -> GridSource {
+        GridSource {
+            frequency: fundamental_freq,
+            ramp_target: fundamental_freq,
+            ramp_rate: 0.0,
+            amplitude,
+            sample_time: 1.0 / sample_rate,
+            theta: 0.0,
+            harmonics: [Harmonic {
+                order: 0,
+                amplitude: 0.0,
+                phase: 0.0,
+            }; MAX_HARMONICS],
+            harmonic_count: 0,
+            sag_depth: 0.0,
+            sag_remaining: 0.0,
+        }
+    }
+    /* Returns false when all harmonic slots are taken */
+    pub fn add_harmonic(&mut self, order: u32, amplitude: f32, phase: f32) -> bool {
+        if self.harmonic_count >= MAX_HARMONICS {
+            return false;
+        }
+        self.harmonics[self.harmonic_count] = Harmonic {
+            order,
+            amplitude,
+            phase,
+        };
+        self.harmonic_count += 1;
+        true
+    }
+    pub fn clear_harmonics(&mut self) {
+        self.harmonic_count = 0;
+    }
+    /* Frequency step, the phase stays continuous */
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+        self.ramp_target = frequency;
+        self.ramp_rate = 0.0;
+    }
+    /* Frequency ramp towards target at rate Hz/s */
+    pub fn ramp_frequency(&mut self, target: f32, rate: f32) {
+        self.ramp_target = target;
+        self.ramp_rate = rate.abs();
+    }
+    pub fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude;
+    }
+    /* Scales the whole waveform by (1 - depth) for duration seconds */
+    pub fn inject_sag(&mut self, depth: f32, duration: f32) {
+        self.sag_depth = depth;
+        self.sag_remaining = duration;
+    }
+    pub fn get_frequency(&self) -> f32 {
+        self.frequency
+    }
+    pub fn get_theta(&self) -> f32 {
+        self.theta
+    }
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> f32 {
+        let mut value = self.amplitude * sin(self.theta);
+        for h in self.harmonics.iter().take(self.harmonic_count) {
+            value += h.amplitude * sin(h.order as f32 * self.theta + h.phase);
+        }
+        if self.sag_remaining > 0.0 {
+            value *= 1.0 - self.sag_depth;
+            self.sag_remaining -= self.sample_time;
+        }
+
+        // Advance the phase, then slew the frequency if a ramp is active
+        self.theta = wrap_2pi(self.theta + TAU * self.frequency * self.sample_time);
+        if self.ramp_rate > 0.0 {
+            let step = self.ramp_rate * self.sample_time;
+            let error = self.ramp_target - self.frequency;
+            if error.abs() <= step {
+                self.frequency = self.ramp_target;
+                self.ramp_rate = 0.0;
+            } else {
+                self.frequency += step.copysign(error);
+            }
+        }
+        value
+    }
+}
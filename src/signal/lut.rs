@@ -0,0 +1,110 @@
+/* Index i of the segment [xs[i], xs[i + 1]] containing x, clamped to the end segments.
+xs must be ascending and hold at least two points */
+fn segment(xs: &[f32], x: f32) -> usize {
+    let mut lo = 0;
+    let mut hi = xs.len() - 1;
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if x < xs[mid] {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    lo
+}
+
+/* Fraction of x along segment i, unclamped */
+fn fraction(xs: &[f32], i: usize, x: f32) -> f32 {
+    let span = xs[i + 1] - xs[i];
+    if span == 0.0 {
+        0.0
+    } else {
+        (x - xs[i]) / span
+    }
+}
+
+/* Piecewise-linear table over ascending, possibly non-uniform, breakpoints */
+pub struct Interp1d<'a> {
+    xs: &'a [f32], /* Breakpoints, ascending */
+    ys: &'a [f32], /* Values at the breakpoints */
+}
+
+impl<'a> Interp1d<'a> {
+    /* Only the first min(xs.len(), ys.len()) points are used */
+    pub fn new(xs: &'a [f32], ys: &'a [f32]) -> Interp1d<'a> {
+        let len = if xs.len() < ys.len() {
+            xs.len()
+        } else {
+            ys.len()
+        };
+        Interp1d {
+            xs: &xs[..len],
+            ys: &ys[..len],
+        }
+    }
+    fn interpolate(&self, x: f32, clamp: bool) -> f32 {
+        match self.xs.len() {
+            0 => 0.0,
+            1 => self.ys[0],
+            _ => {
+                let i = segment(self.xs, x);
+                let mut t = fraction(self.xs, i, x);
+                if clamp {
+                    t = t.clamp(0.0, 1.0);
+                }
+                self.ys[i] + t * (self.ys[i + 1] - self.ys[i])
+            }
+        }
+    }
+    /* Same as eval_clamped */
+    pub fn eval(&self, x: f32) -> f32 {
+        self.interpolate(x, true)
+    }
+    /* Holds the end values outside the table */
+    pub fn eval_clamped(&self, x: f32) -> f32 {
+        self.interpolate(x, true)
+    }
+    /* Extends the end segments linearly outside the table */
+    pub fn eval_extrapolate(&self, x: f32) -> f32 {
+        self.interpolate(x, false)
+    }
+}
+
+/* Bilinear table, zs is row-major with zs[i * ys.len() + j] at (xs[i], ys[j]) */
+pub struct Interp2d<'a> {
+    xs: &'a [f32], /* Row breakpoints, ascending */
+    ys: &'a [f32], /* Column breakpoints, ascending */
+    zs: &'a [f32], /* Values, xs.len() * ys.len() */
+}
+
+impl<'a> Interp2d<'a> {
+    /* Returns None when zs does not hold xs.len() * ys.len() values */
+    pub fn new(xs: &'a [f32], ys: &'a [f32], zs: &'a [f32]) -> Option<Interp2d<'a>> {
+        if xs.is_empty() || ys.is_empty() || zs.len() != xs.len() * ys.len() {
+            return None;
+        }
+        Some(Interp2d { xs, ys, zs })
+    }
+    /* Axis index and fraction, a single breakpoint degenerates to a constant axis */
+    fn locate(axis: &[f32], v: f32) -> (usize, usize, f32) {
+        if axis.len() < 2 {
+            return (0, 0, 0.0);
+        }
+        let i = segment(axis, v);
+        (i, i + 1, fraction(axis, i, v).clamp(0.0, 1.0))
+    }
+    /* Bilinear interpolation, holding the edge values outside the table */
+    pub fn eval(&self, x: f32, y: f32) -> f32 {
+        let n = self.ys.len();
+        let (i0, i1, tx) = Interp2d::locate(self.xs, x);
+        let (j0, j1, ty) = Interp2d::locate(self.ys, y);
+        let z00 = self.zs[i0 * n + j0];
+        let z01 = self.zs[i0 * n + j1];
+        let z10 = self.zs[i1 * n + j0];
+        let z11 = self.zs[i1 * n + j1];
+        let z0 = z00 + ty * (z01 - z00);
+        let z1 = z10 + ty * (z11 - z10);
+        z0 + tx * (z1 - z0)
+    }
+}
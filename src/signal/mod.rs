@@ -1,2 +1,16 @@
+pub mod adc;
+pub mod convert;
+pub mod delay;
+pub mod fft;
 pub mod filter;
 pub mod generator;
+pub mod grid_meter;
+pub mod grid_source;
+pub mod lut;
+pub mod power_meter;
+pub mod pq_events;
+pub mod resample;
+pub mod sample_timing;
+pub mod step_response;
+pub mod window;
+pub mod zero_cross;
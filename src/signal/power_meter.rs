@@ -0,0 +1,146 @@
+use crate::math::float::sqrt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerFactorConvention {
+    Iec,  /* Power factor carries the sign of P, import positive */
+    Ieee, /* Power factor negative for a lagging current, positive for a leading one */
+}
+
+pub struct PowerMeter<const N: usize> {
+    window: usize,                     /* Samples per fundamental cycle */
+    count: usize,                      /* Samples accumulated in the current cycle */
+    sum_p: f32,                        /* Sum of v * i */
+    sum_q: f32,                        /* Sum of v delayed by a quarter cycle * i */
+    sum_v2: f32,                       /* Sum of v^2 */
+    sum_i2: f32,                       /* Sum of i^2 */
+    delay: [f32; N],                   /* Voltage history for the quarter-cycle delay */
+    delay_len: usize,                  /* Quarter-cycle delay in samples, at most N */
+    delay_index: usize,                /* Oldest entry of the delay line */
+    instantaneous: f32,                /* Last v * i */
+    active: f32,                       /* P, one-cycle mean of v * i */
+    reactive: f32,                     /* Q, signed, positive for a lagging (inductive) current */
+    apparent: f32,                     /* S = Vrms * Irms */
+    power_factor: f32,                 /* |P| / S, signed by the convention */
+    v_rms: f32,                        /* One-cycle voltage RMS */
+    i_rms: f32,                        /* One-cycle current RMS */
+    pt_ratio: f32,                     /* Primary per secondary volt of the voltage transformer */
+    ct_ratio: f32,                     /* Primary per secondary amp of the current transformer */
+    convention: PowerFactorConvention, /* Sign rule of the power factor */
+}
+
+impl<const N: usize> PowerMeter<N> {
+    pub fn new(sample_rate: f32, fundamental_freq: f32) -> PowerMeter<N> {
+        let window = (sample_rate / fundamental_freq + 0.5) as usize;
+        let mut delay_len = (0.25 * sample_rate / fundamental_freq + 0.5) as usize;
+        if delay_len > N {
+            delay_len = N;
+        }
+        PowerMeter {
+            window: if window < 1 { 1 } else { window },
+            count: 0,
+            sum_p: 0.0,
+            sum_q: 0.0,
+            sum_v2: 0.0,
+            sum_i2: 0.0,
+            delay: [0.0; N],
+            delay_len,
+            delay_index: 0,
+            instantaneous: 0.0,
+            active: 0.0,
+            reactive: 0.0,
+            apparent: 0.0,
+            power_factor: 0.0,
+            v_rms: 0.0,
+            i_rms: 0.0,
+            convention: PowerFactorConvention::Iec,
+            pt_ratio: 1.0,
+            ct_ratio: 1.0,
+        }
+    }
+    /* Q stays positive for a lagging current under both, only the power factor sign changes */
+    pub fn set_reactive_convention(&mut self, convention: PowerFactorConvention) {
+        self.convention = convention;
+    }
+    /* Inputs are secondary quantities, every output is reported in primary units */
+    pub fn set_transducer_ratios(&mut self, pt: f32, ct: f32) {
+        self.pt_ratio = pt;
+        self.ct_ratio = ct;
+    }
+    pub fn update(&mut self, voltage: f32, current: f32) {
+        let voltage = voltage * self.pt_ratio;
+        let current = current * self.ct_ratio;
+        let v_quarter = if self.delay_len > 0 {
+            let v_quarter = self.delay[self.delay_index];
+            self.delay[self.delay_index] = voltage;
+            self.delay_index += 1;
+            if self.delay_index >= self.delay_len {
+                self.delay_index = 0;
+            }
+            v_quarter
+        } else {
+            voltage
+        };
+
+        self.instantaneous = voltage * current;
+        self.sum_p += self.instantaneous;
+        self.sum_q += v_quarter * current;
+        self.sum_v2 += voltage * voltage;
+        self.sum_i2 += current * current;
+        self.count += 1;
+        if self.count < self.window {
+            return;
+        }
+
+        // One full cycle accumulated, publish the averages
+        let n = self.count as f32;
+        self.active = self.sum_p / n;
+        self.v_rms = sqrt(self.sum_v2 / n);
+        self.i_rms = sqrt(self.sum_i2 / n);
+        self.apparent = self.v_rms * self.i_rms;
+        let q_squared = self.apparent * self.apparent - self.active * self.active;
+        let q = if q_squared > 0.0 {
+            sqrt(q_squared)
+        } else {
+            0.0
+        };
+        // The magnitude comes from the power triangle, the sign from the quadrature product
+        self.reactive = if self.sum_q < 0.0 { -q } else { q };
+        let power_factor = if self.apparent > 0.0 {
+            (self.active / self.apparent).abs()
+        } else {
+            0.0
+        };
+        // Lagging when P and Q share a sign, in either direction of power flow
+        self.power_factor = match self.convention {
+            PowerFactorConvention::Iec => power_factor.copysign(self.active),
+            PowerFactorConvention::Ieee if self.active * self.reactive > 0.0 => -power_factor,
+            PowerFactorConvention::Ieee => power_factor,
+        };
+        self.count = 0;
+        self.sum_p = 0.0;
+        self.sum_q = 0.0;
+        self.sum_v2 = 0.0;
+        self.sum_i2 = 0.0;
+    }
+    pub fn get_instantaneous(&self) -> f32 {
+        self.instantaneous
+    }
+    pub fn get_active(&self) -> f32 {
+        self.active
+    }
+    pub fn get_reactive(&self) -> f32 {
+        self.reactive
+    }
+    pub fn get_apparent(&self) -> f32 {
+        self.apparent
+    }
+    pub fn get_power_factor(&self) -> f32 {
+        self.power_factor
+    }
+    pub fn get_v_rms(&self) -> f32 {
+        self.v_rms
+    }
+    pub fn get_i_rms(&self) -> f32 {
+        self.i_rms
+    }
+}
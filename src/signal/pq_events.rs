@@ -0,0 +1,134 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PqEvent {
+    SagStart,
+    SagEnd { duration: f32 },
+    SwellStart,
+    SwellEnd { duration: f32 },
+    Interruption,
+    InterruptionEnd { duration: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PqState {
+    Normal,
+    Sag,
+    Swell,
+    Interruption,
+}
+
+pub struct PqDetector {
+    nominal_rms: f32,         /* RMS voltage corresponding to 1 pu */
+    sample_time: f32,         /* 1/Frequency of calling update */
+    sag_pu: f32,              /* Sag below this per-unit voltage */
+    swell_pu: f32,            /* Swell above this per-unit voltage */
+    interruption_pu: f32,     /* Interruption below this per-unit voltage */
+    hysteresis_pu: f32,       /* Recovery margin before an event ends */
+    min_duration: f32,        /* Condition must persist this long before it is reported */
+    state: PqState,           /* Latched power-quality state */
+    candidate: PqState,       /* Condition currently being debounced */
+    candidate_time: f32,      /* Time the candidate condition has persisted */
+    duration: f32,            /* Time spent in the current state */
+    pending: Option<PqEvent>, /* Start event deferred by a direct event-to-event transition */
+}
+
+impl PqDetector {
+    pub fn new(nominal_rms: f32, sample_rate: f32) -> PqDetector {
+        PqDetector {
+            nominal_rms,
+            sample_time: 1.0 / sample_rate,
+            sag_pu: 0.9,
+            swell_pu: 1.1,
+            interruption_pu: 0.1,
+            hysteresis_pu: 0.02,
+            min_duration: 0.01,
+            state: PqState::Normal,
+            candidate: PqState::Normal,
+            candidate_time: 0.0,
+            duration: 0.0,
+            pending: None,
+        }
+    }
+    pub fn set_thresholds(&mut self, sag_pu: f32, swell_pu: f32, interruption_pu: f32) {
+        self.sag_pu = sag_pu;
+        self.swell_pu = swell_pu;
+        self.interruption_pu = interruption_pu;
+    }
+    pub fn set_hysteresis(&mut self, hysteresis_pu: f32) {
+        self.hysteresis_pu = hysteresis_pu;
+    }
+    pub fn set_min_duration(&mut self, seconds: f32) {
+        self.min_duration = seconds;
+    }
+    pub fn get_state(&self) -> PqState {
+        self.state
+    }
+    pub fn get_duration(&self) -> f32 {
+        self.duration
+    }
+    fn classify(&self, pu: f32) -> PqState {
+        // The band of the active state is widened by the hysteresis so recovery must be clear
+        let margin = |state: PqState| {
+            if self.state == state {
+                self.hysteresis_pu
+            } else {
+                0.0
+            }
+        };
+        if pu < self.interruption_pu + margin(PqState::Interruption) {
+            PqState::Interruption
+        } else if pu < self.sag_pu + margin(PqState::Sag) {
+            PqState::Sag
+        } else if pu > self.swell_pu - margin(PqState::Swell) {
+            PqState::Swell
+        } else {
+            PqState::Normal
+        }
+    }
+    fn start_event(state: PqState) -> Option<PqEvent> {
+        match state {
+            PqState::Normal => None,
+            PqState::Sag => Some(PqEvent::SagStart),
+            PqState::Swell => Some(PqEvent::SwellStart),
+            PqState::Interruption => Some(PqEvent::Interruption),
+        }
+    }
+    fn end_event(state: PqState, duration: f32) -> Option<PqEvent> {
+        match state {
+            PqState::Normal => None,
+            PqState::Sag => Some(PqEvent::SagEnd { duration }),
+            PqState::Swell => Some(PqEvent::SwellEnd { duration }),
+            PqState::Interruption => Some(PqEvent::InterruptionEnd { duration }),
+        }
+    }
+    pub fn update(&mut self, rms_voltage: f32) -> Option<PqEvent> {
+        self.duration += self.sample_time;
+        let level = self.classify(rms_voltage / self.nominal_rms);
+        if level == self.state {
+            self.candidate = self.state;
+            self.candidate_time = 0.0;
+            return self.pending.take();
+        }
+        if level != self.candidate {
+            self.candidate = level;
+            self.candidate_time = 0.0;
+        }
+        self.candidate_time += self.sample_time;
+        if self.candidate_time < self.min_duration {
+            return self.pending.take();
+        }
+
+        let previous = self.state;
+        let elapsed = self.duration - self.candidate_time;
+        self.state = level;
+        // The debounce time already belongs to the new condition
+        self.duration = self.candidate_time;
+        self.candidate_time = 0.0;
+        match previous {
+            PqState::Normal => PqDetector::start_event(level),
+            _ => {
+                self.pending = PqDetector::start_event(level);
+                PqDetector::end_event(previous, elapsed)
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::signal::filter::biquad::Biquad;
+
+/* One input sample period in the 32.32 fixed-point phase */
+const ONE: u64 = 1 << 32;
+
+pub struct Resampler {
+    step: u64,                  /* Input samples per output sample, 32.32 fixed point */
+    phase: u64,                 /* Position of the next output after the previous input */
+    previous: f32,              /* Input sample before the latest one */
+    latest: f32,                /* Latest input sample */
+    primed: bool,               /* An input sample has been seen since reset */
+    anti_alias: Option<Biquad>, /* Low-pass ahead of decimation */
+}
+
+impl Resampler {
+    pub fn new(input_rate: f32, output_rate: f32) -> Resampler {
+        // Integer phase arithmetic so long runs accumulate no rounding error, only the ratio
+        // itself is quantized to 2^-32 of an input sample
+        let step = (input_rate as f64 / output_rate as f64 * ONE as f64 + 0.5) as u64;
+        Resampler {
+            step: if step < 1 { 1 } else { step },
+            phase: 0,
+            previous: 0.0,
+            latest: 0.0,
+            primed: false,
+            anti_alias: if output_rate < input_rate {
+                Some(Biquad::lowpass(0.4 * output_rate, 0.707, input_rate))
+            } else {
+                None
+            },
+        }
+    }
+    /* Restarts the interpolation from the next sample, for discontinuities in the input */
+    pub fn reset(&mut self) {
+        self.phase = 0;
+        self.previous = 0.0;
+        self.latest = 0.0;
+        self.primed = false;
+        if let Some(filter) = self.anti_alias.as_mut() {
+            filter.reset();
+        }
+    }
+    /* Writes the output samples falling between the previous and this input sample, returns
+    how many were produced. Outputs beyond out.len() are dropped, a slice of
+    ceil(output_rate / input_rate) never overflows. */
+    pub fn process(&mut self, sample: f32, out: &mut [f32]) -> usize {
+        let sample = match self.anti_alias.as_mut() {
+            Some(filter) => filter.process(sample),
+            None => sample,
+        };
+        if !self.primed {
+            // The first sample only starts the interpolation, outputs trail the input by one
+            // input sample
+            self.primed = true;
+            self.latest = sample;
+            return 0;
+        }
+        self.previous = self.latest;
+        self.latest = sample;
+
+        let mut produced = 0;
+        while self.phase < ONE {
+            let fraction = self.phase as f32 / ONE as f32;
+            if let Some(slot) = out.get_mut(produced) {
+                *slot = self.previous + fraction * (self.latest - self.previous);
+                produced += 1;
+            }
+            self.phase += self.step;
+        }
+        self.phase -= ONE;
+        produced
+    }
+}
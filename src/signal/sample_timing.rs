@@ -0,0 +1,72 @@
+use crate::modulation::carrier::CarrierType;
+
+pub struct BlankingWindow {
+    blanking: u32,           /* Ticks after each switching edge where samples are invalid */
+    period: u32,             /* Ticks per switching period */
+    edges: [Option<u32>; 2], /* Switching edge positions within the period */
+}
+
+impl BlankingWindow {
+    /* Starts with a single edge at tick 0, the period boundary */
+    pub fn new(blanking_ticks: u32, period_ticks: u32) -> BlankingWindow {
+        BlankingWindow {
+            blanking: blanking_ticks,
+            period: if period_ticks < 1 { 1 } else { period_ticks },
+            edges: [Some(0), None],
+        }
+    }
+    pub fn set_blanking(&mut self, ticks: u32) {
+        self.blanking = ticks;
+    }
+    /* Edge ticks are taken modulo the period, None for an edge that does not occur */
+    pub fn set_edges(&mut self, first: Option<u32>, second: Option<u32>) {
+        self.edges = [
+            first.map(|e| e % self.period),
+            second.map(|e| e % self.period),
+        ];
+    }
+    /* Places the edges where CarrierComparator switches for this duty and carrier type, duty
+    at 0 or 1 does not switch and leaves the whole period valid */
+    pub fn set_duty(&mut self, duty: f32, carrier_type: CarrierType) {
+        if duty <= 0.0 || duty >= 1.0 {
+            self.edges = [None, None];
+            return;
+        }
+        let period = self.period as f32;
+        let tick = |fraction: f32| Some((fraction * period + 0.5) as u32 % self.period);
+        self.edges = match carrier_type {
+            CarrierType::Sawtooth => [tick(0.0), tick(duty)],
+            CarrierType::Triangular => [tick(0.5 * (1.0 - duty)), tick(0.5 * (1.0 + duty))],
+        };
+    }
+    /* Ticks wrap at the period, so a free-running counter can be passed directly */
+    pub fn should_sample(&self, tick: u32) -> bool {
+        let tick = tick % self.period;
+        self.edges.iter().flatten().all(|&edge| {
+            let elapsed = (tick + self.period - edge) % self.period;
+            elapsed >= self.blanking
+        })
+    }
+    /* Midpoint of the longest stretch outside the blanking windows, None if there is none */
+    pub fn optimal_sample_point(&self) -> Option<u32> {
+        let (start, length) = match self.edges {
+            [None, None] => return Some(self.period / 2),
+            [Some(edge), None] | [None, Some(edge)] => (edge, self.period),
+            [Some(a), Some(b)] => {
+                // Each edge opens a stretch that runs until the next edge
+                let gap_ab = (b + self.period - a) % self.period;
+                let gap_ba = self.period - gap_ab;
+                if gap_ab >= gap_ba {
+                    (a, gap_ab)
+                } else {
+                    (b, gap_ba)
+                }
+            }
+        };
+        if length <= self.blanking {
+            return None;
+        }
+        let valid = length - self.blanking;
+        Some((start + self.blanking + valid / 2) % self.period)
+    }
+}
@@ -0,0 +1,113 @@
+pub struct StepAnalyzer {
+    final_value: f32,     /* Value the response should settle to */
+    tolerance: f32,       /* Settling band as a fraction of the step size */
+    sample_time: f32,     /* Time per sample, 1 reports times in samples */
+    initial: Option<f32>, /* First sample, taken as the value before the step */
+    count: u32,           /* Samples seen, the first one is at time zero */
+    t10: Option<u32>,     /* First sample at 10 % of the step */
+    t90: Option<u32>,     /* First sample at 90 % of the step */
+    peak: f32,            /* Largest normalized excursion towards the final value */
+    in_band: bool,        /* Last sample was inside the settling band */
+    band_entry: u32,      /* Sample that started the current stay inside the band */
+    band_error_sum: f32,  /* Sum of final - sample since band_entry */
+}
+
+impl StepAnalyzer {
+    pub fn new(final_value: f32, tolerance: f32) -> StepAnalyzer {
+        StepAnalyzer {
+            final_value,
+            tolerance: tolerance.abs(),
+            sample_time: 1.0,
+            initial: None,
+            count: 0,
+            t10: None,
+            t90: None,
+            peak: 0.0,
+            in_band: false,
+            band_entry: 0,
+            band_error_sum: 0.0,
+        }
+    }
+    pub fn set_sample_time(&mut self, sample_time: f32) {
+        self.sample_time = sample_time;
+    }
+    pub fn reset(&mut self) {
+        self.initial = None;
+        self.count = 0;
+        self.t10 = None;
+        self.t90 = None;
+        self.peak = 0.0;
+        self.in_band = false;
+        self.band_entry = 0;
+        self.band_error_sum = 0.0;
+    }
+    pub fn update(&mut self, sample: f32) {
+        let initial = *self.initial.get_or_insert(sample);
+        let index = self.count;
+        self.count += 1;
+
+        let step = self.final_value - initial;
+        let progress = if step != 0.0 {
+            (sample - initial) / step
+        } else {
+            1.0
+        };
+        if self.t10.is_none() && progress >= 0.1 {
+            self.t10 = Some(index);
+        }
+        if self.t90.is_none() && progress >= 0.9 {
+            self.t90 = Some(index);
+        }
+        if progress > self.peak {
+            self.peak = progress;
+        }
+
+        // Settling counts from the last exit out of the band, ringing that leaves and
+        // re-enters the band restarts the stay
+        let error = self.final_value - sample;
+        if error.abs() <= self.tolerance * step.abs() {
+            if !self.in_band {
+                self.in_band = true;
+                self.band_entry = index;
+                self.band_error_sum = 0.0;
+            }
+            self.band_error_sum += error;
+        } else {
+            self.in_band = false;
+        }
+    }
+    /* 10 to 90 % rise time */
+    pub fn get_rise_time(&self) -> Option<f32> {
+        match (self.t10, self.t90) {
+            (Some(t10), Some(t90)) => Some((t90 - t10) as f32 * self.sample_time),
+            _ => None,
+        }
+    }
+    /* Peak overshoot in percent of the step */
+    pub fn get_overshoot(&self) -> f32 {
+        if self.peak > 1.0 {
+            100.0 * (self.peak - 1.0)
+        } else {
+            0.0
+        }
+    }
+    /* None while the latest sample is outside the band */
+    pub fn get_settling_time(&self) -> Option<f32> {
+        if self.in_band {
+            Some(self.band_entry as f32 * self.sample_time)
+        } else {
+            None
+        }
+    }
+    /* Mean of final - sample since the response settled */
+    pub fn get_steady_state_error(&self) -> Option<f32> {
+        if self.in_band {
+            Some(self.band_error_sum / (self.count - self.band_entry) as f32)
+        } else {
+            None
+        }
+    }
+    pub fn is_settled(&self) -> bool {
+        self.in_band
+    }
+}
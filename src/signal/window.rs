@@ -0,0 +1,69 @@
+use crate::math::float::cos;
+use core::f32::consts::TAU;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+    FlatTop,
+}
+
+impl Window {
+    /* Cosine-sum coefficients, w[n] = a0 - a1 cos(x) + a2 cos(2x) - a3 cos(3x) + a4 cos(4x) */
+    fn coefficients(&self) -> [f32; 5] {
+        match self {
+            Window::Rectangular => [1.0, 0.0, 0.0, 0.0, 0.0],
+            Window::Hann => [0.5, 0.5, 0.0, 0.0, 0.0],
+            Window::Hamming => [0.54, 0.46, 0.0, 0.0, 0.0],
+            Window::Blackman => [0.42, 0.5, 0.08, 0.0, 0.0],
+            Window::FlatTop => [
+                0.215_578_95,
+                0.416_631_58,
+                0.277_263_16,
+                0.083_578_95,
+                0.006_947_368,
+            ],
+        }
+    }
+    /* Periodic (DFT-even) window value at sample n of len */
+    pub fn value(&self, n: usize, len: usize) -> f32 {
+        let a = self.coefficients();
+        let x = TAU * n as f32 / len as f32;
+        let mut w = a[0];
+        let mut sign = -1.0;
+        for (k, ak) in a.iter().enumerate().skip(1) {
+            if *ak != 0.0 {
+                w += sign * ak * cos(k as f32 * x);
+            }
+            sign = -sign;
+        }
+        w
+    }
+    pub fn apply(&self, samples: &mut [f32]) {
+        let len = samples.len();
+        for (n, sample) in samples.iter_mut().enumerate() {
+            *sample *= self.value(n, len);
+        }
+    }
+    /* Mean of the window, divide windowed amplitude estimates by this */
+    pub fn coherent_gain(&self) -> f32 {
+        self.coefficients()[0]
+    }
+    /* Equivalent noise bandwidth in bins, len * sum(w^2) / sum(w)^2 */
+    pub fn enbw(&self) -> f32 {
+        let a = self.coefficients();
+        let mut power = a[0] * a[0];
+        for ak in a.iter().skip(1) {
+            power += 0.5 * ak * ak;
+        }
+        power / (a[0] * a[0])
+    }
+}
+
+pub fn generate(len: usize, kind: Window, out: &mut [f32]) {
+    for (n, w) in out.iter_mut().take(len).enumerate() {
+        *w = kind.value(n, len);
+    }
+}
@@ -0,0 +1,69 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossDirection {
+    Rising,
+    Falling,
+}
+
+pub struct ZeroCrossDetector {
+    hysteresis: f32,            /* Half-width of the band around zero */
+    polarity: Option<bool>,     /* Side of the band last confirmed, None until first exit */
+    samples_since: u32,         /* Samples since the last confirmed crossing */
+    last_interval: Option<u32>, /* Samples between the last two crossings */
+    crossed: bool,              /* At least one crossing has been confirmed */
+}
+
+impl ZeroCrossDetector {
+    pub fn new(hysteresis: f32) -> ZeroCrossDetector {
+        ZeroCrossDetector {
+            hysteresis: hysteresis.abs(),
+            polarity: None,
+            samples_since: 0,
+            last_interval: None,
+            crossed: false,
+        }
+    }
+    pub fn set_hysteresis(&mut self, hysteresis: f32) {
+        self.hysteresis = hysteresis.abs();
+    }
+    pub fn reset(&mut self) {
+        self.polarity = None;
+        self.samples_since = 0;
+        self.last_interval = None;
+        self.crossed = false;
+    }
+    /* A crossing is confirmed once the signal leaves the band on the opposite side from the
+    last confirmed one, the first exit only establishes the starting polarity */
+    pub fn update(&mut self, sample: f32) -> Option<CrossDirection> {
+        self.samples_since = self.samples_since.saturating_add(1);
+        let side = if sample > self.hysteresis {
+            true
+        } else if sample < -self.hysteresis {
+            false
+        } else {
+            return None;
+        };
+        let previous = self.polarity.replace(side);
+        match previous {
+            Some(p) if p != side => {
+                if self.crossed {
+                    self.last_interval = Some(self.samples_since);
+                }
+                self.crossed = true;
+                self.samples_since = 0;
+                Some(if side {
+                    CrossDirection::Rising
+                } else {
+                    CrossDirection::Falling
+                })
+            }
+            _ => None,
+        }
+    }
+    pub fn samples_since_last_cross(&self) -> u32 {
+        self.samples_since
+    }
+    /* Half-period estimate in samples, available after two crossings */
+    pub fn get_last_interval(&self) -> Option<u32> {
+        self.last_interval
+    }
+}
@@ -0,0 +1,83 @@
+#[derive(Clone, Copy)]
+pub struct ChargeGateConfig {
+    pub cell_v_max: f32,      /* Charging stops when the highest cell reaches this */
+    pub cell_v_min: f32,      /* Discharging stops when the lowest cell reaches this */
+    pub pack_v_max: f32,      /* Charging stops when the pack reaches this */
+    pub pack_v_min: f32,      /* Discharging stops when the pack reaches this */
+    pub v_hysteresis: f32,    /* Cell and pack voltage recovery band */
+    pub charge_temp_min: f32, /* Charge temperature window */
+    pub charge_temp_max: f32,
+    pub discharge_temp_min: f32, /* Discharge temperature window */
+    pub discharge_temp_max: f32,
+    pub temp_hysteresis: f32, /* Temperature recovery band */
+}
+
+pub struct ChargeGate {
+    config: ChargeGateConfig,
+    charge_allowed: bool,
+    discharge_allowed: bool,
+}
+
+/* A gate that is open closes on any violation, a closed gate only reopens once every value
+has recovered past its hysteresis band */
+fn latch(allowed: bool, violated: bool, recovered: bool) -> bool {
+    if allowed {
+        !violated
+    } else {
+        recovered
+    }
+}
+
+impl ChargeGate {
+    pub fn new(config: ChargeGateConfig) -> ChargeGate {
+        ChargeGate {
+            config,
+            charge_allowed: false,
+            discharge_allowed: false,
+        }
+    }
+    pub fn set_config(&mut self, config: ChargeGateConfig) {
+        self.config = config;
+    }
+    pub fn update(
+        &mut self,
+        pack_voltage: f32,
+        min_cell_v: f32,
+        max_cell_v: f32,
+        temp: f32,
+    ) -> (bool, bool) {
+        let c = &self.config;
+
+        let charge_violated = max_cell_v >= c.cell_v_max
+            || pack_voltage >= c.pack_v_max
+            || temp <= c.charge_temp_min
+            || temp >= c.charge_temp_max;
+        let charge_recovered = max_cell_v < c.cell_v_max - c.v_hysteresis
+            && pack_voltage < c.pack_v_max - c.v_hysteresis
+            && temp > c.charge_temp_min + c.temp_hysteresis
+            && temp < c.charge_temp_max - c.temp_hysteresis;
+        self.charge_allowed = latch(self.charge_allowed, charge_violated, charge_recovered);
+
+        let discharge_violated = min_cell_v <= c.cell_v_min
+            || pack_voltage <= c.pack_v_min
+            || temp <= c.discharge_temp_min
+            || temp >= c.discharge_temp_max;
+        let discharge_recovered = min_cell_v > c.cell_v_min + c.v_hysteresis
+            && pack_voltage > c.pack_v_min + c.v_hysteresis
+            && temp > c.discharge_temp_min + c.temp_hysteresis
+            && temp < c.discharge_temp_max - c.temp_hysteresis;
+        self.discharge_allowed = latch(
+            self.discharge_allowed,
+            discharge_violated,
+            discharge_recovered,
+        );
+
+        (self.charge_allowed, self.discharge_allowed)
+    }
+    pub fn is_charge_allowed(&self) -> bool {
+        self.charge_allowed
+    }
+    pub fn is_discharge_allowed(&self) -> bool {
+        self.discharge_allowed
+    }
+}
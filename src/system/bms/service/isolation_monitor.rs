@@ -0,0 +1,110 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IsolationStatus {
+    Ok,
+    Degrading,   /* Above the limit but trending down */
+    Fault,       /* Below the limit */
+    Unavailable, /* No reading from the sensor yet or the last reading was missing */
+}
+
+pub struct IsolationMonitor {
+    limit: f32,          /* Fault below this resistance in kOhm */
+    trend_window: u32,   /* Readings averaged per trend point */
+    trend_fraction: f32, /* Warn when a window average falls this far below the baseline */
+    window_sum: f32,     /* Sum of readings in the current window */
+    window_count: u32,   /* Readings in the current window */
+    baseline: f32,       /* Highest window average seen, 0 until the first window ends */
+    last_average: f32,   /* Most recent window average */
+    resistance: f32,     /* Last valid reading */
+    fault: bool,         /* Latest valid reading is below the limit */
+    degrading: bool,     /* Latest window average shows a drop from the baseline */
+    available: bool,     /* Last update carried a reading */
+}
+
+impl IsolationMonitor {
+    pub fn new(limit_kohm: f32) -> IsolationMonitor {
+        IsolationMonitor {
+            limit: limit_kohm,
+            trend_window: 100,
+            trend_fraction: 0.2,
+            window_sum: 0.0,
+            window_count: 0,
+            baseline: 0.0,
+            last_average: 0.0,
+            resistance: 0.0,
+            fault: false,
+            degrading: false,
+            available: false,
+        }
+    }
+    pub fn set_limit(&mut self, kohm: f32) {
+        self.limit = kohm;
+    }
+    /* Pick the window so it spans minutes at the caller's update rate, insulation
+    degradation is slow compared to measurement noise */
+    pub fn set_trend_window(&mut self, samples: u32) {
+        self.trend_window = if samples < 1 { 1 } else { samples };
+        self.window_sum = 0.0;
+        self.window_count = 0;
+    }
+    pub fn set_trend_fraction(&mut self, fraction: f32) {
+        self.trend_fraction = fraction;
+    }
+    pub fn reset(&mut self) {
+        self.window_sum = 0.0;
+        self.window_count = 0;
+        self.baseline = 0.0;
+        self.last_average = 0.0;
+        self.fault = false;
+        self.degrading = false;
+        self.available = false;
+    }
+    /* A missing reading keeps the last fault and trend state, it never clears a fault */
+    pub fn update(&mut self, resistance_kohm: Option<f32>) -> IsolationStatus {
+        let resistance = match resistance_kohm {
+            Some(r) if r.is_finite() => r,
+            _ => {
+                self.available = false;
+                return self.get_status();
+            }
+        };
+        self.available = true;
+        self.resistance = resistance;
+        self.fault = resistance < self.limit;
+
+        self.window_sum += resistance;
+        self.window_count += 1;
+        if self.window_count >= self.trend_window {
+            self.last_average = self.window_sum / self.window_count as f32;
+            self.window_sum = 0.0;
+            self.window_count = 0;
+            if self.last_average > self.baseline {
+                self.baseline = self.last_average;
+            }
+            self.degrading = self.last_average < self.baseline * (1.0 - self.trend_fraction);
+        }
+        self.get_status()
+    }
+    pub fn get_status(&self) -> IsolationStatus {
+        if self.fault {
+            IsolationStatus::Fault
+        } else if !self.available {
+            IsolationStatus::Unavailable
+        } else if self.degrading {
+            IsolationStatus::Degrading
+        } else {
+            IsolationStatus::Ok
+        }
+    }
+    pub fn is_fault(&self) -> bool {
+        self.fault
+    }
+    pub fn is_degrading(&self) -> bool {
+        self.degrading
+    }
+    pub fn get_resistance(&self) -> f32 {
+        self.resistance
+    }
+    pub fn get_baseline(&self) -> f32 {
+        self.baseline
+    }
+}
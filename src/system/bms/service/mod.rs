@@ -0,0 +1,3 @@
+pub mod charge_policy;
+pub mod isolation_monitor;
+pub mod state_estimation;
@@ -0,0 +1,69 @@
+#[derive(Clone, Copy, PartialEq)]
+pub enum CurrentDirection {
+    Charge,
+    Discharge,
+}
+
+pub struct PackSoc<const N: usize> {
+    direction: CurrentDirection,
+    min_cell_soc: f32,
+    max_cell_soc: f32,
+    pack_soc: f32,
+}
+
+impl<const N: usize> Default for PackSoc<N> {
+    fn default() -> Self {
+        PackSoc::new()
+    }
+}
+
+impl<const N: usize> PackSoc<N> {
+    pub fn new() -> PackSoc<N> {
+        PackSoc {
+            direction: CurrentDirection::Discharge,
+            min_cell_soc: 0.0,
+            max_cell_soc: 0.0,
+            pack_soc: 0.0,
+        }
+    }
+    pub fn set_direction(&mut self, direction: CurrentDirection) {
+        self.direction = direction;
+        self.pack_soc = self.usable_soc();
+    }
+    /* The weakest cell empties first on discharge, the strongest fills first on charge */
+    fn usable_soc(&self) -> f32 {
+        match self.direction {
+            CurrentDirection::Discharge => self.min_cell_soc,
+            CurrentDirection::Charge => self.max_cell_soc,
+        }
+    }
+    pub fn update(&mut self, cell_socs: &[f32; N]) -> f32 {
+        if N == 0 {
+            return self.pack_soc;
+        }
+        self.min_cell_soc = cell_socs[0];
+        self.max_cell_soc = cell_socs[0];
+        for soc in cell_socs.iter().skip(1) {
+            if *soc < self.min_cell_soc {
+                self.min_cell_soc = *soc;
+            }
+            if *soc > self.max_cell_soc {
+                self.max_cell_soc = *soc;
+            }
+        }
+        self.pack_soc = self.usable_soc();
+        self.pack_soc
+    }
+    pub fn get_pack_soc(&self) -> f32 {
+        self.pack_soc
+    }
+    pub fn min_cell_soc(&self) -> f32 {
+        self.min_cell_soc
+    }
+    pub fn max_cell_soc(&self) -> f32 {
+        self.max_cell_soc
+    }
+    pub fn imbalance(&self) -> f32 {
+        self.max_cell_soc - self.min_cell_soc
+    }
+}
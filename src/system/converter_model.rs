@@ -0,0 +1,129 @@
+/* Averaged CCM state-space models, state is x = [i_l, v_out] */
+
+/* One trapezoidal step of x' = A x + f, x[k+1] = (I - A T/2)^-1 ((I + A T/2) x[k] + f T) */
+fn trapezoidal_step(a: [[f32; 2]; 2], f: [f32; 2], x: [f32; 2], t: f32) -> [f32; 2] {
+    let h = 0.5 * t;
+    let rhs = [
+        x[0] + h * (a[0][0] * x[0] + a[0][1] * x[1]) + t * f[0],
+        x[1] + h * (a[1][0] * x[0] + a[1][1] * x[1]) + t * f[1],
+    ];
+    let m00 = 1.0 - h * a[0][0];
+    let m01 = -h * a[0][1];
+    let m10 = -h * a[1][0];
+    let m11 = 1.0 - h * a[1][1];
+    let det = m00 * m11 - m01 * m10;
+    [
+        (m11 * rhs[0] - m01 * rhs[1]) / det,
+        (m00 * rhs[1] - m10 * rhs[0]) / det,
+    ]
+}
+
+fn clamp_duty(duty: f32) -> f32 {
+    duty.clamp(0.0, 1.0)
+}
+
+pub struct BuckConverter {
+    l: f32,           /* Inductance */
+    c: f32,           /* Output capacitance */
+    r_load: f32,      /* Load resistance */
+    v_in: f32,        /* Input voltage */
+    sample_time: f32, /* 1/Frequency of calling step */
+    i_l: f32,         /* Inductor current */
+    v_out: f32,       /* Capacitor voltage */
+}
+
+impl BuckConverter {
+    pub fn new(l: f32, c: f32, r_load: f32, v_in: f32, sample_time: f32) -> BuckConverter {
+        BuckConverter {
+            l,
+            c,
+            r_load,
+            v_in,
+            sample_time,
+            i_l: 0.0,
+            v_out: 0.0,
+        }
+    }
+    pub fn set_load(&mut self, r_load: f32) {
+        self.r_load = r_load;
+    }
+    pub fn set_input_voltage(&mut self, v_in: f32) {
+        self.v_in = v_in;
+    }
+    pub fn reset(&mut self) {
+        self.i_l = 0.0;
+        self.v_out = 0.0;
+    }
+    /* L di/dt = d Vin - v, C dv/dt = i - v/R */
+    pub fn step(&mut self, duty: f32) -> (f32, f32) {
+        let d = clamp_duty(duty);
+        let a = [
+            [0.0, -1.0 / self.l],
+            [1.0 / self.c, -1.0 / (self.r_load * self.c)],
+        ];
+        let f = [d * self.v_in / self.l, 0.0];
+        let x = trapezoidal_step(a, f, [self.i_l, self.v_out], self.sample_time);
+        self.i_l = x[0];
+        self.v_out = x[1];
+        (self.v_out, self.i_l)
+    }
+    pub fn get_v_out(&self) -> f32 {
+        self.v_out
+    }
+    pub fn get_i_l(&self) -> f32 {
+        self.i_l
+    }
+}
+
+pub struct BoostConverter {
+    l: f32,           /* Inductance */
+    c: f32,           /* Output capacitance */
+    r_load: f32,      /* Load resistance */
+    v_in: f32,        /* Input voltage */
+    sample_time: f32, /* 1/Frequency of calling step */
+    i_l: f32,         /* Inductor current */
+    v_out: f32,       /* Capacitor voltage */
+}
+
+impl BoostConverter {
+    pub fn new(l: f32, c: f32, r_load: f32, v_in: f32, sample_time: f32) -> BoostConverter {
+        BoostConverter {
+            l,
+            c,
+            r_load,
+            v_in,
+            sample_time,
+            i_l: 0.0,
+            v_out: v_in,
+        }
+    }
+    pub fn set_load(&mut self, r_load: f32) {
+        self.r_load = r_load;
+    }
+    pub fn set_input_voltage(&mut self, v_in: f32) {
+        self.v_in = v_in;
+    }
+    pub fn reset(&mut self) {
+        self.i_l = 0.0;
+        self.v_out = self.v_in;
+    }
+    /* L di/dt = Vin - (1 - d) v, C dv/dt = (1 - d) i - v/R */
+    pub fn step(&mut self, duty: f32) -> (f32, f32) {
+        let d_off = 1.0 - clamp_duty(duty);
+        let a = [
+            [0.0, -d_off / self.l],
+            [d_off / self.c, -1.0 / (self.r_load * self.c)],
+        ];
+        let f = [self.v_in / self.l, 0.0];
+        let x = trapezoidal_step(a, f, [self.i_l, self.v_out], self.sample_time);
+        self.i_l = x[0];
+        self.v_out = x[1];
+        (self.v_out, self.i_l)
+    }
+    pub fn get_v_out(&self) -> f32 {
+        self.v_out
+    }
+    pub fn get_i_l(&self) -> f32 {
+        self.i_l
+    }
+}
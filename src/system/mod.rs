@@ -0,0 +1,5 @@
+pub mod bms;
+pub mod converter_model;
+pub mod pv_model;
+pub mod thermal;
+pub mod ups;
@@ -0,0 +1,152 @@
+use crate::math::float::exp;
+
+const T_REF: f32 = 298.15; /* STC cell temperature in kelvin */
+const VT_REF: f32 = 0.025_693; /* Thermal voltage kT/q at STC */
+
+pub struct PvArray {
+    isc: f32,          /* Short-circuit current at STC */
+    voc: f32,          /* Open-circuit voltage at STC */
+    imp: f32,          /* Maximum power point current at STC */
+    vmp: f32,          /* Maximum power point voltage at STC */
+    cells_series: u32, /* Cells in series */
+    rs: f32,           /* Fitted series resistance */
+    a_ref: f32,        /* Fitted modified ideality factor n * Ns * Vt at STC */
+    isc_coeff: f32,    /* Relative Isc change per degree C */
+    voc_coeff: f32,    /* Relative Voc change per degree C */
+    irradiance: f32,   /* Irradiance as a fraction of 1000 W/m^2 */
+    temperature: f32,  /* Cell temperature in degrees C */
+    a: f32,            /* Modified ideality factor at the operating temperature */
+    iph: f32,          /* Photo current at the operating point */
+    i0: f32,           /* Diode saturation current at the operating temperature */
+}
+
+/* Saturation and photo current that put the curve through (0, isc) and (voc, 0) */
+fn diode_currents(isc: f32, voc: f32, rs: f32, a: f32) -> (f32, f32) {
+    let i0 = isc / (exp(voc / a) - exp(isc * rs / a));
+    (i0, i0 * (exp(voc / a) - 1.0))
+}
+
+/* Single-diode equation without shunt resistance, I = Iph - I0 (exp((V + I Rs) / a) - 1),
+solved for I by Newton-Raphson */
+fn solve_current(iph: f32, i0: f32, rs: f32, a: f32, v: f32) -> f32 {
+    let mut i = iph;
+    // Start low enough that the exponent stays finite, f(i) < 0 there and the concave
+    // residual makes Newton converge monotonically from above
+    if rs > 0.0 && (v + i * rs) / a > 80.0 {
+        i = (80.0 * a - v) / rs;
+    }
+    for _ in 0..50 {
+        let e = exp((v + i * rs) / a);
+        let f = iph - i0 * (e - 1.0) - i;
+        let df = -i0 * rs / a * e - 1.0;
+        let step = f / df;
+        i -= step;
+        if step.abs() <= 1e-6 * (iph.abs() + 1e-6) {
+            break;
+        }
+    }
+    i
+}
+
+impl PvArray {
+    pub fn new(isc: f32, voc: f32, imp: f32, vmp: f32, cells_series: u32) -> PvArray {
+        let (rs, a_ref) = PvArray::fit(isc, voc, imp, vmp);
+        let mut array = PvArray {
+            isc,
+            voc,
+            imp,
+            vmp,
+            cells_series,
+            rs,
+            a_ref,
+            isc_coeff: 0.0005,
+            voc_coeff: -0.0033,
+            irradiance: 1.0,
+            temperature: 25.0,
+            a: a_ref,
+            iph: isc,
+            i0: 0.0,
+        };
+        array.update_parameters();
+        array
+    }
+    /* Modified ideality that puts the curve through (vmp, imp) for a given Rs */
+    fn fit_ideality(isc: f32, voc: f32, imp: f32, vmp: f32, rs: f32) -> f32 {
+        let mut lo = voc / 80.0;
+        let mut hi = voc;
+        for _ in 0..60 {
+            let a = 0.5 * (lo + hi);
+            let (i0, iph) = diode_currents(isc, voc, rs, a);
+            // A softer knee (larger a) lowers the current at vmp
+            if iph - i0 * (exp((vmp + imp * rs) / a) - 1.0) > imp {
+                lo = a;
+            } else {
+                hi = a;
+            }
+        }
+        0.5 * (lo + hi)
+    }
+    /* Rs and a such that the curve passes through the datasheet MPP with dP/dV = 0 there */
+    fn fit(isc: f32, voc: f32, imp: f32, vmp: f32) -> (f32, f32) {
+        let mpp_slope = |rs: f32| {
+            let a = PvArray::fit_ideality(isc, voc, imp, vmp, rs);
+            let (i0, _) = diode_currents(isc, voc, rs, a);
+            let g = i0 / a * exp((vmp + imp * rs) / a);
+            (imp - vmp * g / (1.0 + rs * g), a)
+        };
+        let mut lo = 0.0;
+        let mut hi = (voc - vmp) / imp;
+        let (slope_lo, a_lo) = mpp_slope(lo);
+        if slope_lo <= 0.0 {
+            // Even without series resistance the knee is too soft, keep the closest fit
+            return (0.0, a_lo);
+        }
+        for _ in 0..40 {
+            let rs = 0.5 * (lo + hi);
+            if mpp_slope(rs).0 > 0.0 {
+                lo = rs;
+            } else {
+                hi = rs;
+            }
+        }
+        let rs = 0.5 * (lo + hi);
+        (rs, mpp_slope(rs).1)
+    }
+    fn update_parameters(&mut self) {
+        let dt = self.temperature - 25.0;
+        let isc = self.isc * (1.0 + self.isc_coeff * dt);
+        let voc = self.voc * (1.0 + self.voc_coeff * dt);
+        self.a = self.a_ref * (self.temperature + 273.15) / T_REF;
+        let (i0, iph) = diode_currents(isc, voc, self.rs, self.a);
+        self.i0 = i0;
+        self.iph = iph * self.irradiance;
+    }
+    pub fn set_irradiance(&mut self, fraction: f32) {
+        self.irradiance = if fraction < 0.0 { 0.0 } else { fraction };
+        self.update_parameters();
+    }
+    pub fn set_temperature(&mut self, celsius: f32) {
+        self.temperature = celsius;
+        self.update_parameters();
+    }
+    pub fn set_temperature_coefficients(&mut self, isc_coeff: f32, voc_coeff: f32) {
+        self.isc_coeff = isc_coeff;
+        self.voc_coeff = voc_coeff;
+        self.update_parameters();
+    }
+    pub fn current_at_voltage(&self, v: f32) -> f32 {
+        solve_current(self.iph, self.i0, self.rs, self.a, v)
+    }
+    pub fn power_at_voltage(&self, v: f32) -> f32 {
+        v * self.current_at_voltage(v)
+    }
+    pub fn get_series_resistance(&self) -> f32 {
+        self.rs
+    }
+    pub fn get_ideality(&self) -> f32 {
+        self.a_ref / (self.cells_series as f32 * VT_REF)
+    }
+    pub fn get_mpp_reference(&self) -> (f32, f32) {
+        (self.vmp, self.imp)
+    }
+}
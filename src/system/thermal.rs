@@ -0,0 +1,98 @@
+use crate::math::float::exp;
+
+const MAX_STAGES: usize = 4;
+
+/* Semiconductor loss at one operating point, conduction from a threshold plus resistance
+model and switching from the total turn-on plus turn-off energy. Scale datasheet energies to
+the operating voltage and current before passing them in. Use v_threshold = 0 for a MOSFET. */
+pub fn conduction_plus_switching_loss(
+    i_avg: f32,
+    i_rms: f32,
+    v_threshold: f32,
+    r_on: f32,
+    switching_energy: f32,
+    f_sw: f32,
+) -> f32 {
+    v_threshold * i_avg + r_on * i_rms * i_rms + switching_energy * f_sw
+}
+
+#[derive(Clone, Copy)]
+struct FosterStage {
+    r: f32,     /* Thermal resistance in K/W */
+    tau: f32,   /* Time constant R * C in seconds */
+    delta: f32, /* Temperature rise across the stage */
+}
+
+/* Foster RC network from junction to ambient as given in device datasheets, Zth(t) =
+sum R_i (1 - exp(-t / tau_i)). Each stage is stepped exactly for a constant loss over dt, so
+the model is stable and exact in steady state for any dt. */
+pub struct JunctionThermalModel {
+    stages: [FosterStage; MAX_STAGES], /* Network stages */
+    count: usize,                      /* Stages in use */
+    ambient: f32,                      /* Reference temperature in degC */
+    junction: f32,                     /* Last junction temperature estimate */
+}
+
+impl JunctionThermalModel {
+    /* rc_network holds (R in K/W, tau in s) per stage, up to 4 stages are used */
+    pub fn new(rc_network: &[(f32, f32)], ambient: f32) -> JunctionThermalModel {
+        let mut model = JunctionThermalModel {
+            stages: [FosterStage {
+                r: 0.0,
+                tau: 0.0,
+                delta: 0.0,
+            }; MAX_STAGES],
+            count: 0,
+            ambient,
+            junction: ambient,
+        };
+        for (stage, &(r, tau)) in model.stages.iter_mut().zip(rc_network.iter()) {
+            stage.r = r;
+            stage.tau = tau;
+            model.count += 1;
+        }
+        model
+    }
+    /* Heatsink or case temperature when the network stops there */
+    pub fn set_ambient(&mut self, temp: f32) {
+        self.ambient = temp;
+    }
+    pub fn reset(&mut self) {
+        for stage in self.stages.iter_mut() {
+            stage.delta = 0.0;
+        }
+        self.junction = self.ambient;
+    }
+    pub fn update(&mut self, power_loss_w: f32, dt: f32) -> f32 {
+        let mut rise = 0.0;
+        for stage in self.stages.iter_mut().take(self.count) {
+            // k = 1 - exp(-dt / tau), from the series when dt << tau where the subtraction
+            // would cancel most of the f32 precision
+            let k = if stage.tau <= 0.0 {
+                1.0
+            } else {
+                let x = dt / stage.tau;
+                if x < 1e-3 {
+                    x * (1.0 - 0.5 * x * (1.0 - x / 3.0))
+                } else {
+                    1.0 - exp(-x)
+                }
+            };
+            stage.delta += k * (stage.r * power_loss_w - stage.delta);
+            rise += stage.delta;
+        }
+        self.junction = self.ambient + rise;
+        self.junction
+    }
+    pub fn get_junction_temperature(&self) -> f32 {
+        self.junction
+    }
+    /* Junction to ambient resistance, the steady-state rise per watt */
+    pub fn get_total_resistance(&self) -> f32 {
+        self.stages
+            .iter()
+            .take(self.count)
+            .map(|stage| stage.r)
+            .sum()
+    }
+}
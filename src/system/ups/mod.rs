@@ -0,0 +1,2 @@
+pub mod off_grid;
+pub mod service;
@@ -0,0 +1,88 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChargeStage {
+    Bulk,       /* Constant current up to the absorption voltage */
+    Absorption, /* Constant voltage until the current tapers */
+    Float,      /* Maintenance voltage */
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChargeCommand {
+    pub stage: ChargeStage,
+    pub voltage_limit: f32, /* Temperature compensated voltage setpoint */
+    pub current_limit: f32, /* Charge current limit */
+}
+
+pub struct MultiStageCharger {
+    bulk_current: f32,       /* Constant current in bulk */
+    absorption_voltage: f32, /* Constant voltage in absorption at the reference temperature */
+    float_voltage: f32,      /* Float voltage at the reference temperature */
+    taper_current: f32,      /* Absorption ends below this current */
+    rebulk_voltage: f32,     /* Float falls back to bulk below this voltage */
+    temp_coeff: f32,         /* Setpoint compensation in V/degC, negative for lead-acid */
+    temp_ref: f32,           /* Temperature the setpoints are specified at */
+    temperature: f32,        /* Last battery temperature */
+    stage: ChargeStage,      /* Active charge stage */
+}
+
+impl MultiStageCharger {
+    pub fn new(
+        bulk_current: f32,
+        absorption_voltage: f32,
+        float_voltage: f32,
+    ) -> MultiStageCharger {
+        MultiStageCharger {
+            bulk_current,
+            absorption_voltage,
+            float_voltage,
+            taper_current: 0.1 * bulk_current,
+            rebulk_voltage: 0.95 * float_voltage,
+            temp_coeff: 0.0,
+            temp_ref: 25.0,
+            temperature: 25.0,
+            stage: ChargeStage::Bulk,
+        }
+    }
+    pub fn set_taper_current(&mut self, current: f32) {
+        self.taper_current = current;
+    }
+    /* Uncompensated, the compensation offset is added when comparing */
+    pub fn set_rebulk_voltage(&mut self, voltage: f32) {
+        self.rebulk_voltage = voltage;
+    }
+    pub fn set_temp_compensation(&mut self, coeff: f32, reference: f32) {
+        self.temp_coeff = coeff;
+        self.temp_ref = reference;
+    }
+    pub fn set_temperature(&mut self, temperature: f32) {
+        self.temperature = temperature;
+    }
+    pub fn reset(&mut self) {
+        self.stage = ChargeStage::Bulk;
+    }
+    pub fn update(&mut self, battery_voltage: f32, battery_current: f32) -> ChargeCommand {
+        let offset = self.temp_coeff * (self.temperature - self.temp_ref);
+        let absorption_voltage = self.absorption_voltage + offset;
+        let float_voltage = self.float_voltage + offset;
+
+        self.stage = match self.stage {
+            ChargeStage::Bulk if battery_voltage >= absorption_voltage => ChargeStage::Absorption,
+            ChargeStage::Absorption if battery_current < self.taper_current => ChargeStage::Float,
+            ChargeStage::Float if battery_voltage < self.rebulk_voltage + offset => {
+                ChargeStage::Bulk
+            }
+            stage => stage,
+        };
+
+        ChargeCommand {
+            stage: self.stage,
+            voltage_limit: match self.stage {
+                ChargeStage::Float => float_voltage,
+                _ => absorption_voltage,
+            },
+            current_limit: self.bulk_current,
+        }
+    }
+    pub fn get_stage(&self) -> ChargeStage {
+        self.stage
+    }
+}
@@ -0,0 +1,119 @@
+/* Loads at this priority are critical and never shed */
+pub const CRITICAL_PRIORITY: u8 = 0;
+
+#[derive(Clone, Copy)]
+struct Load {
+    id: u32,      /* Caller supplied identifier */
+    power: f32,   /* Demand when switched on */
+    priority: u8, /* 0 is critical, higher numbers are shed first */
+    on: bool,     /* Current switch state */
+}
+
+pub struct LoadShedder<const N: usize> {
+    loads: [Load; N], /* Registered loads in insertion order */
+    count: usize,     /* Loads in use */
+    hysteresis: f32,  /* Headroom needed before a shed load is restored */
+    demand: f32,      /* Power of the loads currently on */
+}
+
+impl<const N: usize> LoadShedder<N> {
+    pub fn new() -> LoadShedder<N> {
+        LoadShedder {
+            loads: [Load {
+                id: 0,
+                power: 0.0,
+                priority: 0,
+                on: false,
+            }; N],
+            count: 0,
+            hysteresis: 0.0,
+            demand: 0.0,
+        }
+    }
+    /* Returns false when all slots are taken, the load starts switched on */
+    pub fn add_load(&mut self, id: u32, power: f32, priority: u8) -> bool {
+        if self.count >= N {
+            return false;
+        }
+        self.loads[self.count] = Load {
+            id,
+            power,
+            priority,
+            on: true,
+        };
+        self.count += 1;
+        true
+    }
+    pub fn set_hysteresis(&mut self, margin: f32) {
+        self.hysteresis = margin.abs();
+    }
+    /* Switch states in insertion order, unused slots are false */
+    pub fn update(&mut self, available_power: f32) -> [bool; N] {
+        // Critical loads are always served and come off the budget first
+        let mut used = 0.0;
+        for load in self.loads.iter_mut().take(self.count) {
+            if load.priority == CRITICAL_PRIORITY {
+                load.on = true;
+                used += load.power;
+            }
+        }
+
+        // Walk the remaining loads from the most to the least important, a load that is on
+        // keeps running while it fits, a shed load needs the extra margin to come back. Once
+        // one load does not fit every less important load is shed as well.
+        let mut shedding = false;
+        let mut previous: Option<(u8, usize)> = None;
+        while let Some(index) = self.next_in_order(previous) {
+            let load = &mut self.loads[index];
+            let headroom = if load.on { 0.0 } else { self.hysteresis };
+            if !shedding && used + load.power + headroom <= available_power {
+                load.on = true;
+                used += load.power;
+            } else {
+                load.on = false;
+                shedding = true;
+            }
+            previous = Some((load.priority, index));
+        }
+        self.demand = used;
+        self.get_states()
+    }
+    /* Next non-critical load after previous when ordered by (priority, index) */
+    fn next_in_order(&self, previous: Option<(u8, usize)>) -> Option<usize> {
+        let mut best: Option<(u8, usize)> = None;
+        for (index, load) in self.loads.iter().enumerate().take(self.count) {
+            let key = (load.priority, index);
+            if load.priority == CRITICAL_PRIORITY || previous.is_some_and(|p| key <= p) {
+                continue;
+            }
+            if best.is_none_or(|b| key < b) {
+                best = Some(key);
+            }
+        }
+        best.map(|(_, index)| index)
+    }
+    pub fn get_states(&self) -> [bool; N] {
+        let mut states = [false; N];
+        for (state, load) in states.iter_mut().zip(self.loads.iter().take(self.count)) {
+            *state = load.on;
+        }
+        states
+    }
+    /* None when no load carries this id */
+    pub fn is_load_on(&self, id: u32) -> Option<bool> {
+        self.loads
+            .iter()
+            .take(self.count)
+            .find(|load| load.id == id)
+            .map(|load| load.on)
+    }
+    pub fn get_demand(&self) -> f32 {
+        self.demand
+    }
+}
+
+impl<const N: usize> Default for LoadShedder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,3 @@
+pub mod battery_charging;
+pub mod load_balancing;
+pub mod voltage_regulation;
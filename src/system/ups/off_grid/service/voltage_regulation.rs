@@ -0,0 +1,97 @@
+use crate::control::pid::PID;
+use crate::math::float::wrap_2pi;
+use crate::signal::convert::instantaneous_reference;
+use core::f32::consts::TAU;
+
+/* The PID clock is wrapped at this many seconds so sample steps stay resolvable in f32 */
+const TIME_WRAP: f32 = 64.0;
+
+pub struct VoltageRegulator {
+    nominal_voltage: f32, /* Output RMS setpoint */
+    nominal_freq: f32,    /* Oscillator frequency in Hz */
+    sample_time: f32,     /* 1/Frequency of calling update */
+    max_voltage: f32,     /* Upper bound on the RMS amplitude command */
+    kp: f32,
+    ki: f32,
+    amplitude_loop: PID, /* Correction added to the nominal RMS */
+    time: f32,           /* Wrapped clock fed to the amplitude loop */
+    theta: f32,          /* Oscillator phase in [0, 2pi) */
+    amplitude: f32,      /* RMS amplitude command of the last update */
+}
+
+impl VoltageRegulator {
+    /* Defaults allow the amplitude command to rise 20 % above nominal to ride through load
+    steps */
+    pub fn new(nominal_voltage: f32, nominal_freq: f32, sample_time: f32) -> VoltageRegulator {
+        let mut regulator = VoltageRegulator {
+            nominal_voltage,
+            nominal_freq,
+            sample_time,
+            max_voltage: 1.2 * nominal_voltage,
+            kp: 0.2,
+            ki: 20.0,
+            amplitude_loop: PID::new(0.2, 20.0, 0.0),
+            time: 0.0,
+            theta: 0.0,
+            amplitude: nominal_voltage,
+        };
+        regulator.limits_update();
+        regulator
+    }
+    /* Resets the amplitude loop integral */
+    pub fn set_gains(&mut self, kp: f32, ki: f32) {
+        self.kp = kp;
+        self.ki = ki;
+        self.amplitude_loop = PID::new(kp, ki, 0.0);
+        self.time = 0.0;
+        self.limits_update();
+    }
+    pub fn set_nominal_voltage(&mut self, nominal_voltage: f32) {
+        self.nominal_voltage = nominal_voltage;
+        self.limits_update();
+    }
+    pub fn set_nominal_frequency(&mut self, nominal_freq: f32) {
+        self.nominal_freq = nominal_freq;
+    }
+    pub fn set_max_voltage(&mut self, max_voltage: f32) {
+        self.max_voltage = max_voltage;
+        self.limits_update();
+    }
+    /* The correction and its integral share the bounds, so the integral cannot wind up while
+    the command sits at zero or at max_voltage */
+    fn limits_update(&mut self) {
+        let low = -self.nominal_voltage;
+        let high = (self.max_voltage - self.nominal_voltage).max(low);
+        self.amplitude_loop.set_limits(low, high);
+        self.amplitude_loop.set_integrator_limits(low, high);
+    }
+    pub fn reset(&mut self) {
+        self.set_gains(self.kp, self.ki);
+        self.theta = 0.0;
+        self.amplitude = self.nominal_voltage;
+    }
+    /* Instantaneous voltage reference for the modulation stage, sqrt(2) * amplitude * sin(theta),
+    from the measured output RMS */
+    pub fn update(&mut self, measured_rms: f32) -> f32 {
+        self.time += self.sample_time;
+        if self.time >= TIME_WRAP {
+            self.time -= TIME_WRAP;
+            self.amplitude_loop.shift_time(-TIME_WRAP);
+        }
+        // Nominal feedforward, the loop only supplies the drop across the output impedance
+        let correction = self
+            .amplitude_loop
+            .update(self.nominal_voltage, measured_rms, self.time);
+        self.amplitude = self.nominal_voltage + correction;
+
+        let reference = instantaneous_reference(self.amplitude, self.theta);
+        self.theta = wrap_2pi(self.theta + TAU * self.nominal_freq * self.sample_time);
+        reference
+    }
+    pub fn get_amplitude(&self) -> f32 {
+        self.amplitude
+    }
+    pub fn get_phase(&self) -> f32 {
+        self.theta
+    }
+}
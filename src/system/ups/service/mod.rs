@@ -0,0 +1,2 @@
+pub mod runtime_estimator;
+pub mod transfer_switch;
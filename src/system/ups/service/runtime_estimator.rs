@@ -0,0 +1,90 @@
+use crate::math::float::{exp, ln};
+
+pub struct RuntimeEstimator {
+    capacity_wh: f32,         /* Rated energy at the rated discharge time */
+    rated_hours: f32,         /* Discharge time the capacity is rated at */
+    peukert: f32,             /* Peukert exponent, 1 for an ideal battery */
+    empty_voltage: f32,       /* Rest voltage at 0 % */
+    full_voltage: f32,        /* Rest voltage at 100 % */
+    internal_resistance: f32, /* Used to correct the loaded voltage back to rest */
+    smoothing: f32,           /* Load power filter coefficient, 1 disables filtering */
+    load_power: f32,          /* Filtered load power */
+    remaining: f32,           /* Remaining charge fraction */
+    primed: bool,             /* Load filter has been seeded */
+}
+
+impl RuntimeEstimator {
+    /* Defaults describe a 12 V lead-acid block at the 20 hour rate */
+    pub fn new(battery_capacity_wh: f32) -> RuntimeEstimator {
+        RuntimeEstimator {
+            capacity_wh: battery_capacity_wh,
+            rated_hours: 20.0,
+            peukert: 1.2,
+            empty_voltage: 10.5,
+            full_voltage: 12.7,
+            internal_resistance: 0.0,
+            smoothing: 0.1,
+            load_power: 0.0,
+            remaining: 0.0,
+            primed: false,
+        }
+    }
+    pub fn set_peukert(&mut self, exponent: f32, rated_hours: f32) {
+        self.peukert = exponent;
+        self.rated_hours = rated_hours;
+    }
+    pub fn set_voltage_window(&mut self, empty_voltage: f32, full_voltage: f32) {
+        self.empty_voltage = empty_voltage;
+        self.full_voltage = full_voltage;
+    }
+    pub fn set_internal_resistance(&mut self, ohms: f32) {
+        self.internal_resistance = ohms;
+    }
+    /* First-order load filter, y += alpha (p - y) per update */
+    pub fn set_load_smoothing(&mut self, alpha: f32) {
+        self.smoothing = alpha.clamp(0.0, 1.0);
+    }
+    /* Minutes remaining at the filtered load, None without a load to discharge into */
+    pub fn update(&mut self, battery_voltage: f32, load_power: f32) -> Option<f32> {
+        if self.primed {
+            self.load_power += self.smoothing * (load_power - self.load_power);
+        } else {
+            self.load_power = load_power;
+            self.primed = true;
+        }
+
+        // Estimate the rest voltage by adding back the drop across the internal resistance
+        let current = if battery_voltage > 0.0 {
+            load_power / battery_voltage
+        } else {
+            0.0
+        };
+        let rest_voltage = battery_voltage + self.internal_resistance * current;
+        let span = self.full_voltage - self.empty_voltage;
+        self.remaining = if span > 0.0 {
+            ((rest_voltage - self.empty_voltage) / span).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        if self.load_power <= 0.0 {
+            return None;
+        }
+        // Peukert, a load above the rated power drains the capacity faster than linearly
+        let rated_power = self.capacity_wh / self.rated_hours;
+        let effective_wh =
+            self.capacity_wh * exp((self.peukert - 1.0) * ln(rated_power / self.load_power));
+        Some(60.0 * self.remaining * effective_wh / self.load_power)
+    }
+    pub fn remaining_percent(&self) -> f32 {
+        100.0 * self.remaining
+    }
+    pub fn get_load_power(&self) -> f32 {
+        self.load_power
+    }
+    pub fn reset(&mut self) {
+        self.load_power = 0.0;
+        self.remaining = 0.0;
+        self.primed = false;
+    }
+}
@@ -0,0 +1,102 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Mains,
+    Inverter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferMode {
+    MakeBeforeBreak, /* Both sources connected for the transition time */
+    BreakBeforeMake, /* Neither source connected for the transition time */
+}
+
+pub struct TransferSwitch {
+    sample_time: f32,      /* 1/Frequency of calling update */
+    transfer_delay: f32,   /* Mains must be bad this long before moving to the inverter */
+    retransfer_delay: f32, /* Mains must be good this long before moving back */
+    min_dwell: f32,        /* Minimum time on a source before the next transfer */
+    transition_time: f32,  /* Overlap or gap duration of a transfer */
+    mode: TransferMode,
+    source: Source,       /* Source selected */
+    pending_time: f32,    /* Time the condition for a transfer has persisted */
+    dwell_time: f32,      /* Time since the last transfer */
+    transition_left: f32, /* Remaining overlap or gap of the last transfer */
+}
+
+impl TransferSwitch {
+    pub fn new(sample_time: f32) -> TransferSwitch {
+        TransferSwitch {
+            sample_time,
+            transfer_delay: 0.0,
+            retransfer_delay: 5.0,
+            min_dwell: 1.0,
+            transition_time: 0.0,
+            mode: TransferMode::BreakBeforeMake,
+            source: Source::Mains,
+            pending_time: 0.0,
+            dwell_time: 0.0,
+            transition_left: 0.0,
+        }
+    }
+    pub fn set_delays(&mut self, transfer_delay: f32, retransfer_delay: f32) {
+        self.transfer_delay = transfer_delay;
+        self.retransfer_delay = retransfer_delay;
+    }
+    pub fn set_min_dwell(&mut self, seconds: f32) {
+        self.min_dwell = seconds;
+    }
+    pub fn set_transition(&mut self, mode: TransferMode, seconds: f32) {
+        self.mode = mode;
+        self.transition_time = seconds;
+    }
+    pub fn update(&mut self, mains_present: bool, mains_quality_ok: bool) -> Source {
+        self.dwell_time += self.sample_time;
+        if self.transition_left > 0.0 {
+            self.transition_left -= self.sample_time;
+        }
+
+        let mains_ok = mains_present && mains_quality_ok;
+        let (wanted, delay) = match self.source {
+            Source::Mains => (!mains_ok, self.transfer_delay),
+            Source::Inverter => (mains_ok, self.retransfer_delay),
+        };
+        if !wanted {
+            self.pending_time = 0.0;
+            return self.source;
+        }
+        self.pending_time += self.sample_time;
+        // A failed mains is left as soon as the delay allows, the dwell time only holds
+        // back a return to mains
+        let dwell_ok = self.source == Source::Mains || self.dwell_time >= self.min_dwell;
+        if self.pending_time >= delay && dwell_ok {
+            self.source = match self.source {
+                Source::Mains => Source::Inverter,
+                Source::Inverter => Source::Mains,
+            };
+            self.pending_time = 0.0;
+            self.dwell_time = 0.0;
+            self.transition_left = self.transition_time;
+        }
+        self.source
+    }
+    pub fn get_source(&self) -> Source {
+        self.source
+    }
+    pub fn in_transition(&self) -> bool {
+        self.transition_left > 0.0
+    }
+    /* (mains connected, inverter connected) including the transfer overlap or gap */
+    pub fn get_outputs(&self) -> (bool, bool) {
+        let selected = (
+            self.source == Source::Mains,
+            self.source == Source::Inverter,
+        );
+        if !self.in_transition() {
+            return selected;
+        }
+        match self.mode {
+            TransferMode::MakeBeforeBreak => (true, true),
+            TransferMode::BreakBeforeMake => (false, false),
+        }
+    }
+}
@@ -1 +1,49 @@
+const FRAC_1_SQRT_3: f32 = 0.577_350_26;
 
+/* Fused Clarke and Park, amplitude-invariant scaling (2/3), so a balanced set of peak
+amplitude A aligned with theta gives d = A, q = 0. The zero sequence is (a + b + c) / 3 and
+q leads d as in park::Park with the default convention. */
+pub fn abc_to_dq(a: f32, b: f32, c: f32, sin_theta: f32, cos_theta: f32) -> (f32, f32, f32) {
+    let alpha = (2.0 / 3.0) * (a - 0.5 * (b + c));
+    let beta = FRAC_1_SQRT_3 * (b - c);
+    let zero = (a + b + c) / 3.0;
+    let d = alpha * cos_theta + beta * sin_theta;
+    let q = beta * cos_theta - alpha * sin_theta;
+    (d, q, zero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::float::{cos, sin};
+    use crate::transform::dq0_abc::dq_to_abc;
+
+    #[test]
+    fn abc_to_dq_then_dq_to_abc_recovers_abc_with_zero_sequence() {
+        for step in 0..16 {
+            let theta = -3.0 + 0.4 * step as f32;
+            // Unbalanced set, a + b + c stays well away from zero at every step
+            let (a, b, c) = (0.9 - 0.05 * step as f32, -0.2 + 0.03 * step as f32, 0.05);
+            let (sin_theta, cos_theta) = (sin(theta), cos(theta));
+            let (d, q, zero) = abc_to_dq(a, b, c, sin_theta, cos_theta);
+            assert!((zero - (a + b + c) / 3.0).abs() < 1e-6);
+            let (a_out, b_out, c_out) = dq_to_abc(d, q, zero, sin_theta, cos_theta);
+            assert!((a_out - a).abs() < 1e-4);
+            assert!((b_out - b).abs() < 1e-4);
+            assert!((c_out - c).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn balanced_set_aligned_with_theta_is_dc() {
+        for step in 0..8 {
+            let theta = 0.7 * step as f32;
+            let third = core::f32::consts::TAU / 3.0;
+            let (a, b, c) = (cos(theta), cos(theta - third), cos(theta + third));
+            let (d, q, zero) = abc_to_dq(a + 0.1, b + 0.1, c + 0.1, sin(theta), cos(theta));
+            assert!((d - 1.0).abs() < 1e-4);
+            assert!(q.abs() < 1e-4);
+            assert!((zero - 0.1).abs() < 1e-4);
+        }
+    }
+}
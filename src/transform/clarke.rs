@@ -5,6 +5,11 @@ pub struct Clarke {
     alpha: f32,
     beta: f32,
     zero: f32,
+    scale: f32,        /* Gain from input units, e.g. ADC counts, to engineering units */
+    offsets: [f32; 3], /* Per-phase sensor offsets in input units */
+    cal_samples: u32,  /* Samples averaged by one calibration */
+    cal_count: u32,    /* Samples accumulated by the running calibration */
+    cal_sum: [f32; 3], /* Per-phase calibration accumulators */
 }
 
 impl Clarke {
@@ -16,11 +21,82 @@ impl Clarke {
             alpha: alpha,
             beta: beta,
             zero: 0.0,
+            scale: 1.0,
+            offsets: [0.0; 3],
+            cal_samples: 64,
+            cal_count: 0,
+            cal_sum: [0.0; 3],
         }
     }
+    pub fn with_scale(scale: f32) -> Clarke {
+        let mut clarke = Clarke::new(0.0, 0.0);
+        clarke.scale = scale;
+        clarke
+    }
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+    pub fn get_scale(&self) -> f32 {
+        self.scale
+    }
+    /* Subtracted from the raw inputs before scaling */
+    pub fn set_offsets(&mut self, a_off: f32, b_off: f32, c_off: f32) {
+        self.offsets = [a_off, b_off, c_off];
+    }
+    pub fn get_offsets(&self) -> (f32, f32, f32) {
+        (self.offsets[0], self.offsets[1], self.offsets[2])
+    }
+    /* Zeroes the offsets and abandons a running calibration */
+    pub fn clear_offsets(&mut self) {
+        self.offsets = [0.0; 3];
+        self.cal_count = 0;
+        self.cal_sum = [0.0; 3];
+    }
+    pub fn set_calibration_samples(&mut self, samples: u32) {
+        self.cal_samples = if samples < 1 { 1 } else { samples };
+    }
+    /* Feed raw samples taken at zero current, returns true once the average over the
+    configured number of samples has been stored as the offsets. The next call starts a new
+    calibration, the previous offsets stay in use until then. */
+    pub fn auto_calibrate(&mut self, a: f32, b: f32, c: f32) -> bool {
+        self.cal_sum[0] += a;
+        self.cal_sum[1] += b;
+        self.cal_sum[2] += c;
+        self.cal_count += 1;
+        if self.cal_count < self.cal_samples {
+            return false;
+        }
+        let n = self.cal_count as f32;
+        for (offset, sum) in self.offsets.iter_mut().zip(self.cal_sum.iter()) {
+            *offset = sum / n;
+        }
+        self.cal_count = 0;
+        self.cal_sum = [0.0; 3];
+        true
+    }
+    pub fn is_calibrating(&self) -> bool {
+        self.cal_count > 0
+    }
+    pub fn set_abc(&mut self, a: f32, b: f32, c: f32) {
+        self.a = a;
+        self.b = b;
+        self.c = c;
+    }
     pub fn calculate(&mut self) {
-        self.alpha = ((2.0 / 3.0) * self.a) - ((1.0 / 3.0) * (self.b - self.c));
-        self.beta = (2.0 / 1.732) * (self.b - self.c);
+        let a = self.scale * (self.a - self.offsets[0]);
+        let b = self.scale * (self.b - self.offsets[1]);
+        let c = self.scale * (self.c - self.offsets[2]);
+        self.alpha = ((2.0 / 3.0) * a) - ((1.0 / 3.0) * (b - c));
+        self.beta = (2.0 / 1.732) * (b - c);
         self.zero = 0.0;
     }
+    pub fn get_alpha(&self) -> f32 {
+        self.alpha
+    }
+    pub fn get_beta(&self) -> f32 {
+        self.beta
+    }
+    pub fn get_zero(&self) -> f32 {
+        self.zero
+    }
 }
@@ -1 +1,14 @@
+const FRAC_1_SQRT_3: f32 = 0.577_350_26;
 
+/* Inverse of abc_dq0::abc_to_dq, amplitude-invariant scaling with the zero sequence added to
+every phase */
+pub fn dq_to_abc(d: f32, q: f32, zero: f32, sin_theta: f32, cos_theta: f32) -> (f32, f32, f32) {
+    let alpha = d * cos_theta - q * sin_theta;
+    let beta = d * sin_theta + q * cos_theta;
+    // sqrt(3) / 2 = 1.5 / sqrt(3)
+    let half_sqrt3_beta = 1.5 * FRAC_1_SQRT_3 * beta;
+    let a = alpha + zero;
+    let b = -0.5 * alpha + half_sqrt3_beta + zero;
+    let c = -0.5 * alpha - half_sqrt3_beta + zero;
+    (a, b, c)
+}
@@ -1,3 +1,6 @@
+use super::park::ParkConvention;
+use crate::math::float::{cos, sin, wrap_pi};
+
 pub struct IPark {
     alpha: f32,
     beta: f32,
@@ -7,6 +10,11 @@ pub struct IPark {
     d: f32,
     q: f32,
     z: f32,
+    convention: ParkConvention,
+    angle_offset: f32, /* Added to every supplied angle, e.g. encoder to d-axis alignment */
+    angle_advance: f32, /* Delay compensation added on top of the offset */
+    offset_sin: f32,   /* Sine of the total rotation, offset plus advance */
+    offset_cos: f32,   /* Cosine of the total rotation, offset plus advance */
 }
 
 impl IPark {
@@ -20,10 +28,73 @@ impl IPark {
             d: 0.0,
             q: 0.0,
             z: 0.0,
+            convention: ParkConvention::QLeading,
+            angle_offset: 0.0,
+            angle_advance: 0.0,
+            offset_sin: 0.0,
+            offset_cos: 1.0,
         }
     }
+    /* Must match the convention of the forward Park transform for the pair to round-trip */
+    pub fn set_convention(&mut self, convention: ParkConvention) {
+        self.convention = convention;
+    }
+    pub fn get_convention(&self) -> ParkConvention {
+        self.convention
+    }
+    pub fn set_dq(&mut self, d: f32, q: f32) {
+        self.d = d;
+        self.q = q;
+    }
+    /* Applies to both set_angle and set_sin_cos, use the same offset on the forward and
+    inverse transforms */
+    pub fn set_angle_offset(&mut self, radians: f32) {
+        self.angle_offset = radians;
+        self.rotation_update();
+    }
+    pub fn get_angle_offset(&self) -> f32 {
+        self.angle_offset
+    }
+    /* Advances the applied angle by omega * sample_time * compensation_samples to cover the
+    delay between sampling the angle and applying the voltage, zero samples disables it. Call
+    before set_angle or set_sin_cos when omega changes. */
+    pub fn set_angle_advance(&mut self, omega: f32, sample_time: f32, compensation_samples: u32) {
+        self.angle_advance = omega * sample_time * compensation_samples as f32;
+        self.rotation_update();
+    }
+    pub fn get_angle_advance(&self) -> f32 {
+        self.angle_advance
+    }
+    fn rotation_update(&mut self) {
+        let rotation = self.rotation();
+        self.offset_sin = sin(rotation);
+        self.offset_cos = cos(rotation);
+    }
+    fn rotation(&self) -> f32 {
+        wrap_pi(self.angle_offset + self.angle_advance)
+    }
+    pub fn set_sin_cos(&mut self, sin: f32, cos: f32) {
+        // Rotate the supplied angle by the offset and advance
+        self.sin = sin * self.offset_cos + cos * self.offset_sin;
+        self.cos = cos * self.offset_cos - sin * self.offset_sin;
+    }
+    pub fn set_angle(&mut self, theta: f32) {
+        let theta = wrap_pi(theta + self.rotation());
+        self.sin = sin(theta);
+        self.cos = cos(theta);
+    }
     pub fn calculate(&mut self) {
-        self.alpha = self.d * self.cos - self.q * self.sin;
-        self.beta = self.q * self.cos + self.d * self.sin;
+        let sin = match self.convention {
+            ParkConvention::QLeading => self.sin,
+            ParkConvention::QLagging => -self.sin,
+        };
+        self.alpha = self.d * self.cos - self.q * sin;
+        self.beta = self.q * self.cos + self.d * sin;
+    }
+    pub fn get_alpha(&self) -> f32 {
+        self.alpha
+    }
+    pub fn get_beta(&self) -> f32 {
+        self.beta
     }
 }
@@ -1,3 +1,19 @@
+use crate::math::float::{cos, sin};
+
+/* QLagging also reverses the rotation direction of the frame: a balanced set whose vector
+turns with +theta is DC under QLeading, but under QLagging it appears at twice the angle and
+only a set turning with -theta is DC */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParkConvention {
+    QLeading, /* q axis 90 degrees ahead of d, d = a cos + b sin, q = b cos - a sin */
+    QLagging, /* q axis 90 degrees behind d, d = a cos - b sin, q = b cos + a sin */
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransformError {
+    UnnormalizedAngle, /* sin^2 + cos^2 is outside 1 +/- tolerance, or not finite */
+}
+
 pub struct Park {
     alpha: f32,
     beta: f32,
@@ -7,6 +23,11 @@ pub struct Park {
     d: f32,
     q: f32,
     z: f32,
+    convention: ParkConvention,
+    angle_offset: f32, /* Added to every supplied angle, e.g. encoder to d-axis alignment */
+    offset_sin: f32,   /* Sine of angle_offset */
+    offset_cos: f32,   /* Cosine of angle_offset */
+    angle_tolerance: f32, /* Accepted deviation of sin^2 + cos^2 from 1 in try_calculate */
 }
 
 impl Park {
@@ -20,11 +41,123 @@ impl Park {
             d: 0.0,
             q: 0.0,
             z: 0.0,
+            convention: ParkConvention::QLeading,
+            angle_offset: 0.0,
+            offset_sin: 0.0,
+            offset_cos: 1.0,
+            angle_tolerance: 1e-3,
         }
     }
+    pub fn set_convention(&mut self, convention: ParkConvention) {
+        self.convention = convention;
+    }
+    pub fn get_convention(&self) -> ParkConvention {
+        self.convention
+    }
+    pub fn set_alpha_beta(&mut self, alpha: f32, beta: f32) {
+        self.alpha = alpha;
+        self.beta = beta;
+    }
+    /* Applies to both set_angle and set_sin_cos, use the same offset on the forward and
+    inverse transforms */
+    pub fn set_angle_offset(&mut self, radians: f32) {
+        self.angle_offset = radians;
+        self.offset_sin = sin(radians);
+        self.offset_cos = cos(radians);
+    }
+    pub fn get_angle_offset(&self) -> f32 {
+        self.angle_offset
+    }
+    pub fn set_sin_cos(&mut self, sin: f32, cos: f32) {
+        // Rotate the supplied angle by the offset
+        self.sin = sin * self.offset_cos + cos * self.offset_sin;
+        self.cos = cos * self.offset_cos - sin * self.offset_sin;
+    }
+    pub fn set_angle(&mut self, theta: f32) {
+        self.sin = sin(theta + self.angle_offset);
+        self.cos = cos(theta + self.angle_offset);
+    }
     pub fn calculate(&mut self) {
-        self.d = self.alpha * self.cos + self.beta * self.sin;
-        self.q = self.beta * self.cos - self.alpha * self.sin;
+        let sin = match self.convention {
+            ParkConvention::QLeading => self.sin,
+            ParkConvention::QLagging => -self.sin,
+        };
+        self.d = self.alpha * self.cos + self.beta * sin;
+        self.q = self.beta * self.cos - self.alpha * sin;
         self.z = 0.0;
     }
+    pub fn set_angle_tolerance(&mut self, tolerance: f32) {
+        self.angle_tolerance = tolerance.abs();
+    }
+    /* calculate with a check that sin/cos describe an angle, the outputs are left untouched
+    on error */
+    pub fn try_calculate(&mut self) -> Result<(), TransformError> {
+        let norm = self.sin * self.sin + self.cos * self.cos;
+        // Also rejects a NaN pair, which fails every comparison
+        if (norm - 1.0).abs() > self.angle_tolerance || !norm.is_finite() {
+            return Err(TransformError::UnnormalizedAngle);
+        }
+        self.calculate();
+        Ok(())
+    }
+    pub fn get_d(&self) -> f32 {
+        self.d
+    }
+    pub fn get_q(&self) -> f32 {
+        self.q
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::ipark::IPark;
+
+    #[test]
+    fn park_then_ipark_recovers_alpha_beta_in_both_conventions() {
+        for convention in [ParkConvention::QLeading, ParkConvention::QLagging] {
+            let mut park = Park::new(0.0, 0.0);
+            let mut ipark = IPark::new(0.0, 0.0);
+            park.set_convention(convention);
+            ipark.set_convention(convention);
+            for step in 0..16 {
+                let theta = -3.0 + 0.4 * step as f32;
+                let (alpha, beta) = (0.8 - 0.1 * step as f32, 0.3 + 0.05 * step as f32);
+                park.set_alpha_beta(alpha, beta);
+                park.set_angle(theta);
+                park.calculate();
+                ipark.set_dq(park.get_d(), park.get_q());
+                ipark.set_angle(theta);
+                ipark.calculate();
+                assert!((ipark.get_alpha() - alpha).abs() < 1e-4);
+                assert!((ipark.get_beta() - beta).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn q_lagging_reverses_the_rotation_direction() {
+        let mut leading = Park::new(0.0, 0.0);
+        let mut lagging = Park::new(0.0, 0.0);
+        lagging.set_convention(ParkConvention::QLagging);
+        for step in 0..8 {
+            // Unit vector turning with +theta, DC when leading, at twice the angle when lagging
+            let theta = 0.7 * step as f32;
+            for park in [&mut leading, &mut lagging] {
+                park.set_alpha_beta(cos(theta), sin(theta));
+                park.set_angle(theta);
+                park.calculate();
+            }
+            assert!((leading.get_d() - 1.0).abs() < 1e-4);
+            assert!(leading.get_q().abs() < 1e-4);
+            assert!((lagging.get_d() - cos(2.0 * theta)).abs() < 1e-4);
+            assert!((lagging.get_q() - sin(2.0 * theta)).abs() < 1e-4);
+
+            // The same set turning with -theta is DC under QLagging
+            lagging.set_alpha_beta(cos(theta), -sin(theta));
+            lagging.calculate();
+            assert!((lagging.get_d() - 1.0).abs() < 1e-4);
+            assert!(lagging.get_q().abs() < 1e-4);
+        }
+    }
 }